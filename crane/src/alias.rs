@@ -0,0 +1,150 @@
+use anyhow::anyhow;
+
+use crate::config::{Alias, AliasEntry, BrickEntry};
+
+/// A requested brick, or an alias with the bricks and aliases it contains
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expanded {
+    Brick(BrickEntry),
+    Alias {
+        name: String,
+        entries: Vec<Expanded>,
+    },
+}
+
+impl Expanded {
+    /// All bricks of the tree, in order
+    pub fn bricks(nodes: &[Expanded]) -> Vec<BrickEntry> {
+        nodes
+            .iter()
+            .flat_map(|node| match node {
+                Expanded::Brick(entry) => vec![entry.clone()],
+                Expanded::Alias { entries, .. } => Expanded::bricks(entries),
+            })
+            .collect()
+    }
+}
+
+fn find_alias<'a>(aliases: &'a [Alias], name: &str) -> Option<&'a Alias> {
+    aliases
+        .iter()
+        .find(|alias| alias.name().to_lowercase() == name.to_lowercase())
+}
+
+/// Expand all aliases in the queries, including aliases inside aliases.
+///
+/// Every brick is only included once, at its first occurrence.
+/// Fails if an alias contains itself.
+pub fn expand_aliases(
+    queries: &[AliasEntry],
+    aliases: &[Alias],
+) -> anyhow::Result<Vec<Expanded>> {
+    let mut seen = Vec::new();
+    let mut stack = Vec::new();
+    let mut nodes = Vec::new();
+    for query in queries {
+        if let Some(node) = expand_query(query, aliases, &mut stack, &mut seen)? {
+            nodes.push(node);
+        }
+    }
+    Ok(nodes)
+}
+
+fn expand_query(
+    query: &AliasEntry,
+    aliases: &[Alias],
+    stack: &mut Vec<String>,
+    seen: &mut Vec<String>,
+) -> anyhow::Result<Option<Expanded>> {
+    // Only plain names can refer to other aliases
+    let alias = match query {
+        AliasEntry::Name(name) => find_alias(aliases, name),
+        AliasEntry::Brick(_) => None,
+    };
+    let Some(alias) = alias else {
+        let entry = query.to_brick();
+        let name = entry.brick.to_lowercase();
+        if seen.contains(&name) {
+            return Ok(None);
+        }
+        seen.push(name);
+        return Ok(Some(Expanded::Brick(entry)));
+    };
+    if stack
+        .iter()
+        .any(|name| name.to_lowercase() == alias.name().to_lowercase())
+    {
+        stack.push(alias.name().to_string());
+        return Err(anyhow!(
+            "Alias '{}' contains itself: {}",
+            alias.name(),
+            stack.join(" → ")
+        ));
+    }
+    stack.push(alias.name().to_string());
+    let mut entries = Vec::new();
+    for entry in alias.bricks() {
+        if let Some(node) = expand_query(entry, aliases, stack, seen)? {
+            entries.push(node);
+        }
+    }
+    stack.pop();
+    if entries.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(Expanded::Alias {
+        name: alias.name().to_string(),
+        entries,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alias(name: &str, bricks: &[&str]) -> Alias {
+        Alias::new(name.to_string(), bricks.to_vec())
+    }
+
+    fn brick(name: &str) -> Expanded {
+        Expanded::Brick(AliasEntry::from(name).to_brick())
+    }
+
+    #[test]
+    fn test_expand_nested_aliases() {
+        let aliases = vec![
+            alias("rust", &["rust-base", "ci", "mit"]),
+            alias("ci", &["github-ci", "rust-base"]),
+        ];
+        let expanded =
+            expand_aliases(&["rust".into(), "MIT:x".into()], &aliases).unwrap();
+        assert_eq!(
+            expanded,
+            vec![Expanded::Alias {
+                name: String::from("rust"),
+                entries: vec![
+                    brick("rust-base"),
+                    Expanded::Alias {
+                        name: String::from("ci"),
+                        entries: vec![brick("github-ci")],
+                    },
+                    brick("mit"),
+                ],
+            }]
+        );
+        assert_eq!(
+            Expanded::bricks(&expanded)
+                .iter()
+                .map(|entry| entry.brick.as_str())
+                .collect::<Vec<&str>>(),
+            vec!["rust-base", "github-ci", "mit"]
+        );
+    }
+
+    #[test]
+    fn test_expand_alias_cycle() {
+        let aliases = vec![alias("a", &["x", "b"]), alias("b", &["a"])];
+        let error = expand_aliases(&["a".into()], &aliases).unwrap_err();
+        assert!(error.to_string().contains("a → b → a"), "{}", error);
+    }
+}
@@ -1,27 +1,29 @@
-use std::{env, path::Path};
+use std::{collections::HashMap, env, fmt::Display, path::Path};
 
+use anyhow::anyhow;
 use colored::Colorize;
 use log::debug;
 
 use crate::{
-    cmd::{Add, Run},
-    config::CraneConfig,
+    alias::{Expanded, expand_aliases},
+    cmd::{Add, Format, Run, load_bricks},
+    config::{AliasEntry, BrickEntry, CraneConfig},
+    diff, fuzzy, output,
+    picker::{self, PickerItem},
+    prompt,
 };
 use crane_bricks::{
-    brick::{Brick, bricks_in_dir},
+    brick::{Brick, Selection},
     context::ActionContext,
+    event::Event,
+    project::ProjectRecord,
+    resolve::{check_conflicts, find_brick, resolve_requirements},
 };
 
 impl Run for Add {
     fn run(&self) {
         let config = CraneConfig::new();
-        let brick_dirs = if let Some(brick_dirs) = &self.brick_dirs
-            && brick_dirs.is_empty()
-        {
-            brick_dirs
-        } else {
-            &config.brick_dirs().to_vec()
-        };
+        let brick_dirs = &config.brick_dirs_or(&self.brick_dirs);
         debug!(
             "Checking brick dirs:\n* {}",
             brick_dirs
@@ -36,10 +38,8 @@ impl Run for Add {
             None => &env::current_dir().unwrap(),
         };
 
-        let bricks: Vec<Brick> = brick_dirs
-            .iter()
-            .flat_map(|dir| bricks_in_dir(dir))
-            .collect();
+        let bricks: Vec<Brick> =
+            brick_dirs.iter().flat_map(|dir| load_bricks(dir)).collect();
 
         debug!(
             "Found bricks:\n* {}",
@@ -50,73 +50,471 @@ impl Run for Add {
                 .join("\n* ")
         );
 
-        let brick_queries: Vec<String> = self
-            .bricks
+        let aliases = config.aliases(brick_dirs);
+        let names: Vec<&str> = bricks
             .iter()
-            .flat_map(|query| {
-                for alias in config.alias() {
-                    if alias.name().to_lowercase() == query.to_lowercase() {
-                        return alias.bricks().to_vec();
-                    }
+            .map(|brick| brick.name())
+            .chain(aliases.iter().map(|alias| alias.name()))
+            .collect();
+        let text = self.format == Format::Text;
+
+        // Without bricks, let the user pick them
+        let picked = self.bricks.is_empty();
+        let requested = if picked {
+            if !prompt::is_interactive() || !text {
+                self.error("No bricks to add");
+                return;
+            }
+            let mut items: Vec<PickerItem> =
+                bricks.iter().map(PickerItem::from_brick).collect();
+            items.extend(aliases.iter().map(PickerItem::from_alias));
+            match picker::pick(items) {
+                Ok(Some(names)) if !names.is_empty() => names,
+                Ok(_) => return,
+                Err(error) => {
+                    self.error(error);
+                    return;
                 }
-                vec![query.clone()]
-            })
+            }
+        } else {
+            self.bricks.clone()
+        };
+        let interactive =
+            (self.interactive || picked) && text && prompt::is_interactive();
+
+        let queries: Vec<AliasEntry> = requested
+            .iter()
+            .filter_map(|query| self.correct_query(query, &names, config.auto_select()))
+            .map(AliasEntry::from)
             .collect();
+        let expanded = match expand_aliases(&queries, &aliases) {
+            Ok(expanded) => expanded,
+            Err(error) => {
+                self.error(error);
+                return;
+            }
+        };
 
-        let mut bricks_to_execute: Vec<&Brick> = Vec::new();
-        for brick_query in brick_queries {
-            let mut found = false;
-            for brick in &bricks {
-                if brick.name().to_lowercase() == brick_query.to_lowercase() {
-                    bricks_to_execute.push(brick);
-                    found = true;
-                    break;
+        let mut requested_bricks: Vec<&Brick> = Vec::new();
+        let mut entries: HashMap<String, BrickEntry> = HashMap::new();
+        for entry in Expanded::bricks(&expanded) {
+            match find_brick(&bricks, &entry.brick) {
+                Some(brick) => {
+                    requested_bricks.push(brick);
+                    entries.insert(brick.name().to_string(), entry);
                 }
+                None => self.not_found(
+                    &entry.brick,
+                    &fuzzy::suggestions(
+                        &entry.brick,
+                        bricks.iter().map(|brick| brick.name()),
+                    ),
+                ),
             }
-            if !found {
-                eprintln!("{} Could not find brick '{}'", "⚠".red(), brick_query);
-            }
-        }
-        /* TODO: render aliases like this:
-        → Executing 4 bricks
-          • MIT
-          • rust (alias)
-            ◦ author-rust
-            ◦ serde
-            • rustfmt
-        */
-        let plural = if bricks_to_execute.len() > 1 { "s" } else { "" };
-        println!(
-            "{} Executing {} brick{}",
-            "→".green(),
-            bricks_to_execute.len().to_string().purple(),
-            plural
-        );
+        }
+
+        let bricks_to_execute = match resolve_requirements(&requested_bricks, &bricks) {
+            Ok(bricks) => bricks,
+            Err(error) => {
+                self.error(error);
+                return;
+            }
+        };
+
+        let mut outdated = false;
         for brick in &bricks_to_execute {
-            println!("  {} {}", "•".dimmed(), brick.name())
+            if let Err(error) = brick
+                .config()
+                .check_crane_version(env!("CARGO_PKG_VERSION"))
+            {
+                self.error(error);
+                outdated = true;
+            }
+        }
+        if outdated {
+            return;
+        }
+
+        let mut record = match ProjectRecord::load(target_dir) {
+            Ok(record) => record,
+            Err(error) => {
+                self.error(format!("Could not read applied bricks: {}", error));
+                return;
+            }
+        };
+        if let Err(error) = check_conflicts(&bricks_to_execute, record.applied()) {
+            if text {
+                eprintln!("{} Refusing to add conflicting bricks", "⚠".red());
+                for line in error.to_string().lines() {
+                    eprintln!("  {} {}", "•".dimmed(), line);
+                }
+            } else {
+                self.error(format!("Refusing to add conflicting bricks: {}", error));
+            }
+            return;
+        }
+
+        let bricks_to_execute =
+            match self.select(&bricks_to_execute, &entries, interactive) {
+                Ok(bricks) => bricks,
+                Err(error) => {
+                    self.error(error);
+                    return;
+                }
+            };
+        if text {
+            let plural = if bricks_to_execute.len() > 1 { "s" } else { "" };
+            println!(
+                "{} Executing {} brick{}",
+                "→".green(),
+                bricks_to_execute.len().to_string().purple(),
+                plural
+            );
+            for brick in &bricks_to_execute {
+                if !requested_bricks
+                    .iter()
+                    .any(|requested| requested.name() == brick.name())
+                {
+                    println!(
+                        "  {} {}{}",
+                        "•".dimmed(),
+                        brick_label(brick),
+                        " (required)".dimmed()
+                    )
+                }
+            }
+            print_tree(&expanded, &bricks_to_execute, 1);
         }
 
-        let context = ActionContext::new(self.dry_run);
-        for brick in bricks_to_execute {
-            execute_brick(brick, &context, target_dir);
+        let variables = match self.variables(&bricks_to_execute, &entries, interactive) {
+            Ok(variables) => variables,
+            Err(error) => {
+                self.error(error);
+                return;
+            }
+        };
+
+        // Picked bricks are previewed before anything is changed
+        if picked && !self.dry_run {
+            println!("\n{} Previewing changes", "→".green());
+            let preview = ActionContext::new(true).with_bricks(bricks.clone());
+            for brick in &bricks_to_execute {
+                if let Err(error) = brick
+                    .execute(&preview.child(variables[brick.name()].clone()), target_dir)
+                {
+                    eprintln!("  {} {}", "⚠".red(), error);
+                }
+            }
+            diff::print_planned(target_dir, &preview.planned_files());
+            println!();
+            match prompt::confirm("Apply these changes?") {
+                Ok(true) => {}
+                Ok(false) => return,
+                Err(error) => {
+                    self.error(error);
+                    return;
+                }
+            }
         }
+
+        let mut context = ActionContext::new(self.dry_run).with_bricks(bricks.clone());
+        if !text {
+            let format = self.format;
+            context =
+                context.with_listener(move |event| output::print_event(format, event));
+        }
+        let mut applied = Vec::new();
+        for brick in &bricks_to_execute {
+            let context = context.child(variables[brick.name()].clone());
+            let succeeded = if text {
+                execute_brick(brick, &context, target_dir)
+            } else {
+                brick.execute(&context, target_dir).is_ok()
+            };
+            if succeeded {
+                applied.push(brick);
+            }
+        }
+        if let Err(error) = record_applied(&mut record, &applied, &context, target_dir) {
+            self.error(format!("Could not save applied bricks: {}", error));
+        }
+    }
+}
+
+/// Remember the successfully executed bricks in the project, nothing is saved
+/// if no brick succeeded
+fn record_applied(
+    record: &mut ProjectRecord,
+    applied: &[&Brick],
+    context: &ActionContext,
+    target_dir: &Path,
+) -> anyhow::Result<()> {
+    if applied.is_empty() {
+        return Ok(());
     }
+    for brick in applied {
+        record.add(brick);
+    }
+    record.save(context, target_dir)
 }
 
-fn execute_brick(brick: &Brick, context: &ActionContext, cwd: &Path) {
+impl Add {
+    /// Variables for every brick. Alias entries set the lowest precedence,
+    /// then the answers of the user if running interactively, then `--var`.
+    fn variables(
+        &self,
+        bricks: &[Brick],
+        entries: &HashMap<String, BrickEntry>,
+        interactive: bool,
+    ) -> anyhow::Result<HashMap<String, HashMap<String, String>>> {
+        let given: HashMap<String, String> = self.variables.iter().cloned().collect();
+        // Bricks with the same variable share the answer
+        let mut answers: HashMap<String, String> = HashMap::new();
+        let mut variables = HashMap::new();
+        for brick in bricks {
+            let mut brick_variables = entries
+                .get(brick.name())
+                .map(|entry| entry.variables.clone())
+                .unwrap_or_default();
+            if interactive {
+                for variable in brick.config().variables() {
+                    let name = variable.name();
+                    if given.contains_key(name) || brick_variables.contains_key(name) {
+                        continue;
+                    }
+                    if !answers.contains_key(name) {
+                        answers.insert(
+                            name.to_string(),
+                            prompt::input_variable(brick, variable)?,
+                        );
+                    }
+                    brick_variables.insert(name.to_string(), answers[name].clone());
+                }
+            }
+            brick_variables.extend(given.clone());
+            variables.insert(brick.name().to_string(), brick_variables);
+        }
+        Ok(variables)
+    }
+
+    /// Select the variant and features of every brick, asking the user
+    /// if running interactively
+    fn select(
+        &self,
+        bricks: &[&Brick],
+        entries: &HashMap<String, BrickEntry>,
+        interactive: bool,
+    ) -> anyhow::Result<Vec<Brick>> {
+        for feature in &self.features {
+            if !bricks
+                .iter()
+                .any(|brick| brick.config().feature(feature).is_some())
+            {
+                return Err(anyhow!("None of the bricks has a feature '{}'", feature));
+            }
+        }
+        bricks
+            .iter()
+            .map(|brick| {
+                let entry = entries.get(brick.name());
+                let variant = match entry.and_then(|entry| entry.variant.clone()) {
+                    Some(variant) => Some(variant),
+                    None if interactive => prompt::select_variant(brick)?,
+                    None => None,
+                };
+                let mut features: Vec<String> = entry
+                    .map(|entry| entry.features.clone())
+                    .unwrap_or_default();
+                if interactive && self.features.is_empty() && features.is_empty() {
+                    features = prompt::select_features(brick)?
+                } else {
+                    features.extend(
+                        self.features
+                            .iter()
+                            .filter(|feature| brick.config().feature(feature).is_some())
+                            .filter(|feature| !features.contains(feature))
+                            .cloned()
+                            .collect::<Vec<String>>(),
+                    );
+                }
+                brick.select(&Selection::new(variant, features))
+            })
+            .collect()
+    }
+
+    /// Report an error, as event if the output is structured
+    fn error(&self, error: impl Display) {
+        match self.format {
+            Format::Text => eprintln!("{} {}", "⚠".red(), error),
+            format => output::print_event(
+                format,
+                &Event::Error {
+                    message: error.to_string(),
+                },
+            ),
+        }
+    }
+
+    /// Check that a brick or alias with the name of the query exists. If not,
+    /// the closest name is used if it's the only match and auto select is enabled.
+    fn correct_query(
+        &self,
+        query: &str,
+        names: &[&str],
+        auto_select: bool,
+    ) -> Option<String> {
+        let (name, variant) = match query.split_once(':') {
+            Some((name, variant)) => (name, Some(variant)),
+            None => (query, None),
+        };
+        if names
+            .iter()
+            .any(|existing| existing.to_lowercase() == name.to_lowercase())
+        {
+            return Some(query.to_string());
+        }
+        let matches = fuzzy::rank(name, names.iter().copied());
+        if auto_select && matches.len() == 1 {
+            if self.format == Format::Text {
+                println!("{} Using '{}' for '{}'", "→".green(), matches[0], name);
+            }
+            return Some(match variant {
+                Some(variant) => format!("{}:{}", matches[0], variant),
+                None => matches[0].to_string(),
+            });
+        }
+        self.not_found(name, &fuzzy::suggestions(name, matches));
+        None
+    }
+
+    /// Report that a brick doesn't exist, with the names the user might have meant
+    fn not_found(&self, name: &str, suggestions: &[&str]) {
+        if suggestions.is_empty() {
+            self.error(format!("Could not find brick '{}'", name));
+        } else {
+            self.error(format!(
+                "Could not find brick '{}', did you mean '{}'?",
+                name,
+                suggestions.join("', '")
+            ));
+        }
+    }
+}
+
+/// Print the requested bricks, with the bricks of aliases nested below them
+fn print_tree(nodes: &[Expanded], bricks: &[Brick], depth: usize) {
+    let indent = "  ".repeat(depth);
+    let bullet = if depth == 1 { "•" } else { "◦" };
+    for node in nodes {
+        match node {
+            Expanded::Brick(entry) => {
+                if let Some(brick) = find_brick(bricks, &entry.brick) {
+                    println!("{}{} {}", indent, bullet.dimmed(), brick_label(brick));
+                }
+            }
+            Expanded::Alias { name, entries } => {
+                // Aliases only containing bricks that were not found are hidden
+                if !Expanded::bricks(entries)
+                    .iter()
+                    .any(|entry| find_brick(bricks, &entry.brick).is_some())
+                {
+                    continue;
+                }
+                println!(
+                    "{}{} {}{}",
+                    indent,
+                    bullet.dimmed(),
+                    name,
+                    " (alias)".dimmed()
+                );
+                print_tree(entries, bricks, depth + 1);
+            }
+        }
+    }
+}
+
+/// Name of the brick with its selected variant and features
+fn brick_label(brick: &Brick) -> String {
+    let mut label = brick.name().to_string();
+    if let Some(variant) = &brick.selection().variant {
+        label.push_str(&format!(":{}", variant));
+    }
+    if !brick.selection().features.is_empty() {
+        label.push_str(&format!(
+            " (with {})",
+            brick.selection().features.join(", ")
+        ));
+    }
+    label
+}
+
+/// Execute a brick and print the result, returns if it succeeded
+fn execute_brick(brick: &Brick, context: &ActionContext, cwd: &Path) -> bool {
     println!(
         "\n{} Executing brick '{}'",
         "→".green(),
-        brick.name().purple()
+        brick_label(brick).purple()
     );
     match brick.execute(context, cwd) {
-        Ok(_) => println!(
-            "{}",
-            format!("✔ Successfully executed '{}'! ◝(°ᗜ°)◜", brick.name().bold()).green()
-        ),
-        Err(_) => eprintln!(
-            "{}",
-            format!("✘ Failed to execute '{}'! ヽ(°〇°)ﾉ", brick.name().bold()).red()
-        ),
+        Ok(_) => {
+            println!(
+                "{}",
+                format!("✔ Successfully executed '{}'! ◝(°ᗜ°)◜", brick.name().bold())
+                    .green()
+            );
+            true
+        }
+        Err(error) => {
+            eprintln!("  {} {}", "⚠".red(), error);
+            eprintln!(
+                "{}",
+                format!("✘ Failed to execute '{}'! ヽ(°〇°)ﾉ", brick.name().bold()).red()
+            );
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crane_bricks::{brick::BrickConfig, project::PROJECT_DIR};
+
+    use super::*;
+
+    fn brick(config: &str) -> Brick {
+        let config: BrickConfig = toml::from_str(config).unwrap();
+        Brick::new_with_config(config, PathBuf::from("brick"))
+    }
+
+    fn add(bricks: &[Brick], target_dir: &Path) {
+        let context = ActionContext::new(false);
+        let mut record = ProjectRecord::load(target_dir).unwrap();
+        let applied: Vec<&Brick> = bricks
+            .iter()
+            .filter(|brick| brick.execute(&context, target_dir).is_ok())
+            .collect();
+        record_applied(&mut record, &applied, &context, target_dir).unwrap();
+    }
+
+    #[test]
+    fn test_record_applied() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let failing = brick(
+            "name = \"failing\"\n[[actions]]\naction = \"chmod\"\nsources = [\"missing\"]\nmode = \"0600\"",
+        );
+        let working = brick(
+            "name = \"working\"\n[[actions]]\naction = \"create_dir\"\npath = \"docs\"",
+        );
+
+        // A failed add leaves no record behind
+        add(std::slice::from_ref(&failing), tmpdir.path());
+        assert!(!tmpdir.path().join(PROJECT_DIR).exists());
+
+        add(&[failing, working], tmpdir.path());
+        let record = ProjectRecord::load(tmpdir.path()).unwrap();
+        assert_eq!(record.applied().len(), 1);
+        assert_eq!(record.applied()[0].name(), "working");
     }
 }
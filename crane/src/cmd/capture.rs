@@ -0,0 +1,327 @@
+use std::{
+    cmp::Reverse,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::anyhow;
+use colored::Colorize;
+use log::{error, info, warn};
+
+use crate::{
+    cmd::{
+        Capture, Run,
+        new::{brick_toml, new_brick_dir},
+    },
+    config::CraneConfig,
+    snapshot::project_files,
+};
+use crane_bricks::{
+    actions::{Action, insert_file::InsertFileAction},
+    brick::{BRICK_CONFIG_FILE, BrickConfig, Variable},
+    file_utils::is_binary,
+};
+
+/// A project file that is copied into the brick
+#[derive(Debug)]
+struct Captured {
+    source: PathBuf,
+    /// Path relative to the project, which becomes the brick file name
+    name: String,
+    templated: bool,
+}
+
+impl Run for Capture {
+    fn run(&self) {
+        let config = CraneConfig::new();
+        let Some(brick_dir) = self
+            .brick_dir
+            .as_ref()
+            .or_else(|| config.brick_dirs().first())
+        else {
+            error!("No brick directory is configured, choose one with --in <dir>");
+            return;
+        };
+        let path = match new_brick_dir(brick_dir, &self.name) {
+            Ok(path) => path,
+            Err(error) => {
+                error!("{}", error);
+                return;
+            }
+        };
+        let Ok(cwd) = env::current_dir() else {
+            error!("Could not read the current directory");
+            return;
+        };
+        let captured = match self.capture(&cwd, &path) {
+            Ok(captured) => captured,
+            Err(error) => {
+                error!("Could not capture brick: {}", error);
+                return;
+            }
+        };
+
+        let plural = if captured.len() == 1 { "" } else { "s" };
+        println!(
+            "{} Captured {} file{} into brick '{}' at {}",
+            "→".green(),
+            captured.len().to_string().purple(),
+            plural,
+            self.name.trim(),
+            path.display()
+        );
+        for file in &captured {
+            let templated = if file.templated { " (templated)" } else { "" };
+            info!("{}{}", file.name, templated.dimmed());
+        }
+        for (value, name) in &self.replacements {
+            info!("Variable '{}' defaults to {:?}", name, value);
+        }
+    }
+}
+
+impl Capture {
+    /// Copy the files, relative to `cwd`, into the new brick at `path` and
+    /// write its config. Nothing is left behind if it fails.
+    fn capture(&self, cwd: &Path, path: &Path) -> anyhow::Result<Vec<Captured>> {
+        let captured = self.write_brick(cwd, path);
+        if captured.is_err() {
+            // Don't leave a half captured brick behind
+            let _ = fs::remove_dir_all(path);
+        }
+        captured
+    }
+
+    fn write_brick(&self, cwd: &Path, path: &Path) -> anyhow::Result<Vec<Captured>> {
+        let cwd = cwd.canonicalize()?;
+        let mut captured: Vec<Captured> = Vec::new();
+        for file in &self.files {
+            let source = cwd.join(file).canonicalize().map_err(|error| {
+                anyhow!("Could not read '{}': {}", file.display(), error)
+            })?;
+            let relative = source.strip_prefix(&cwd).map_err(|_| {
+                anyhow!("'{}' is not inside the current directory", file.display())
+            })?;
+            collect(&source, relative, &mut captured)?;
+        }
+        if captured.is_empty() {
+            return Err(anyhow!("No files to capture"));
+        }
+
+        fs::create_dir_all(path)?;
+        for file in &mut captured {
+            let target = path.join(&file.name);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let content = fs::read(&file.source)?;
+            let content = match String::from_utf8(content) {
+                Ok(text) if !is_binary(text.as_bytes()) => {
+                    let templated = templated(&text, &self.replacements);
+                    file.templated = templated != text;
+                    templated.into_bytes()
+                }
+                Ok(text) => text.into_bytes(),
+                Err(error) => error.into_bytes(),
+            };
+            fs::write(&target, content)?;
+            fs::set_permissions(&target, fs::metadata(&file.source)?.permissions())?;
+        }
+
+        let variables = self
+            .replacements
+            .iter()
+            .map(|(value, name)| Variable::new(name.clone(), Some(value.clone()), None))
+            .collect();
+        let config = BrickConfig::new(
+            self.name.trim().to_string(),
+            vec![Action::InsertFile(InsertFileAction::default())],
+        )
+        .with_variables(variables);
+        fs::write(path.join(BRICK_CONFIG_FILE), brick_toml(&config)?)?;
+        Ok(captured)
+    }
+}
+
+/// Add the file at `source`, or all files in it if it's a directory
+fn collect(
+    source: &Path,
+    relative: &Path,
+    captured: &mut Vec<Captured>,
+) -> anyhow::Result<()> {
+    // Brick file names always use `/`
+    let name = relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    if source.is_dir() {
+        for (file_name, path) in project_files(source)? {
+            let file_name = if name.is_empty() {
+                file_name
+            } else {
+                format!("{}/{}", name, file_name)
+            };
+            add(path, file_name, captured)?;
+        }
+        return Ok(());
+    }
+    if !source.is_file() {
+        warn!("Skipping '{}', it's not a file", relative.display());
+        return Ok(());
+    }
+    add(source.to_path_buf(), name, captured)
+}
+
+fn add(
+    source: PathBuf,
+    name: String,
+    captured: &mut Vec<Captured>,
+) -> anyhow::Result<()> {
+    if name == BRICK_CONFIG_FILE {
+        return Err(anyhow!(
+            "'{}' can't be captured, bricks use it for their config",
+            name
+        ));
+    }
+    if !captured.iter().any(|file| file.name == name) {
+        captured.push(Captured {
+            source,
+            name,
+            templated: false,
+        });
+    }
+    Ok(())
+}
+
+/// Replace every occurrence of the values with a placeholder for their
+/// variable, preferring the longest value at each position
+fn templated(text: &str, replacements: &[(String, String)]) -> String {
+    let mut replacements: Vec<&(String, String)> = replacements.iter().collect();
+    replacements.sort_by_key(|(value, _)| Reverse(value.len()));
+    let mut output = String::new();
+    let mut rest = text;
+    while let Some(char) = rest.chars().next() {
+        match replacements
+            .iter()
+            .find(|(value, _)| !value.is_empty() && rest.starts_with(value.as_str()))
+        {
+            Some((value, name)) => {
+                output.push_str(&format!("{{{{ {} }}}}", name));
+                rest = &rest[value.len()..];
+            }
+            None => {
+                output.push(char);
+                rest = &rest[char.len_utf8()..];
+            }
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_templated() {
+        let replacements = vec![
+            (String::from("crane"), String::from("name")),
+            (String::from("crane_bricks"), String::from("library")),
+        ];
+        assert_eq!(
+            templated(
+                "[package]\nname = \"crane\"\ncrane_bricks = 1",
+                &replacements
+            ),
+            "[package]\nname = \"{{ name }}\"\n{{ library }} = 1"
+        );
+        assert_eq!(templated("nothing to do", &replacements), "nothing to do");
+    }
+
+    fn capture(files: &[&str]) -> Capture {
+        Capture {
+            name: String::from("captured"),
+            files: files.iter().map(PathBuf::from).collect(),
+            brick_dir: None,
+            replacements: vec![(String::from("demo"), String::from("name"))],
+        }
+    }
+
+    #[test]
+    fn test_capture() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let project = tmpdir.path().join("demo");
+        for (name, content) in [
+            ("Cargo.toml", "[package]\nname = \"demo\"\n"),
+            ("src/main.rs", "fn main() {}\n"),
+            ("src/nested/demo.rs", "// demo\n"),
+            ("src/target/debug.log", "skipped\n"),
+            ("docs/brick.toml", "name = \"demo\"\n"),
+        ] {
+            let path = project.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        fs::write(project.join("src/demo.ico"), b"\0\0demo\x01").unwrap();
+
+        // Directories and files keep their path relative to the project
+        let path = tmpdir.path().join("bricks/captured");
+        let captured = capture(&["src", "docs/brick.toml", "Cargo.toml"])
+            .capture(&project, &path)
+            .unwrap();
+        let names: Vec<(&str, bool)> = captured
+            .iter()
+            .map(|file| (file.name.as_str(), file.templated))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("src/demo.ico", false),
+                ("src/main.rs", false),
+                ("src/nested/demo.rs", true),
+                ("docs/brick.toml", true),
+                ("Cargo.toml", true),
+            ]
+        );
+        assert_eq!(
+            fs::read_to_string(path.join("src/nested/demo.rs")).unwrap(),
+            "// {{ name }}\n"
+        );
+        // Binary files are copied as they are
+        assert_eq!(
+            fs::read(path.join("src/demo.ico")).unwrap(),
+            b"\0\0demo\x01"
+        );
+        assert!(!path.join("src/target").exists());
+        assert!(path.join(BRICK_CONFIG_FILE).exists());
+
+        // A brick.toml at the top of the brick would be its config
+        let path = tmpdir.path().join("bricks/config");
+        for files in [["brick.toml"], ["."]] {
+            let error = capture(&files)
+                .capture(&project.join("docs"), &path)
+                .unwrap_err();
+            assert!(error.to_string().contains("can't be captured"), "{}", error);
+            assert!(!path.exists());
+        }
+
+        // Files outside the project can't be captured
+        fs::write(tmpdir.path().join("outside.txt"), "").unwrap();
+        let error = capture(&["../outside.txt"])
+            .capture(&project, &path)
+            .unwrap_err();
+        assert!(error.to_string().contains("not inside"), "{}", error);
+
+        // The brick is removed if capturing fails halfway
+        let path = tmpdir.path().join("bricks/failed");
+        fs::create_dir_all(&path).unwrap();
+        fs::write(path.join("src"), "in the way").unwrap();
+        assert!(
+            capture(&["Cargo.toml", "src"])
+                .capture(&project, &path)
+                .is_err()
+        );
+        assert!(!path.exists());
+    }
+}
@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use clap::{Parser, Subcommand, ValueHint, command};
+use clap::{Parser, Subcommand, ValueEnum, ValueHint};
 use clap_verbosity::{InfoLevel, Verbosity};
 
 #[derive(Debug, Parser)]
@@ -17,14 +17,30 @@ pub struct CraneCli {
 pub enum CraneCommand {
     Add(Add),
     List(List),
+    Search(Search),
+    Show(Show),
+    New(New),
+    Capture(Capture),
+    Record(Record),
+    Validate(Validate),
 }
 
 /// Add a brick to your directory
 #[derive(Debug, Parser)]
 pub struct Add {
-    #[clap(num_args = 1.., required = true)]
+    /// Bricks or aliases to add, select a variant with `brick:variant`.
+    /// Without any, the bricks can be picked interactively.
+    #[clap(num_args = 0..)]
     pub bricks: Vec<String>,
 
+    /// Features to add, for all bricks that have them
+    #[arg(short, long = "with", value_delimiter = ',')]
+    pub features: Vec<String>,
+
+    /// Ask for variants and features of the bricks
+    #[arg(short, long)]
+    pub interactive: bool,
+
     #[arg(short, long, value_hint=ValueHint::DirPath, value_terminator=",")]
     pub brick_dirs: Option<Vec<PathBuf>>,
 
@@ -33,11 +49,173 @@ pub struct Add {
 
     #[arg(short = 'n', long)]
     pub dry_run: bool,
+
+    /// Set a variable used by the bricks, e.g. `--var author=ACME`
+    #[arg(long = "var", value_name = "KEY=VALUE", value_parser = parse_variable)]
+    pub variables: Vec<(String, String)>,
+
+    /// Print events as JSON Lines (or YAML documents) instead of text
+    #[arg(long, value_enum, default_value_t)]
+    pub format: Format,
+}
+
+/// How commands print their results
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    #[default]
+    Text,
+    Json,
+    Yaml,
+}
+
+/// Parse a `VALUE=NAME` replacement, splitting at the last `=`
+fn parse_replacement(value: &str) -> Result<(String, String), String> {
+    match value.rsplit_once('=') {
+        Some((text, name)) if !text.is_empty() && !name.is_empty() => {
+            Ok((text.to_string(), name.to_string()))
+        }
+        _ => Err(format!("Expected VALUE=NAME, got '{}'", value)),
+    }
+}
+
+fn parse_variable(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("Expected KEY=VALUE, got '{}'", value)),
+    }
 }
 
 /// List all available bricks
 #[derive(Debug, Parser, Clone)]
 pub struct List {
+    /// Only list bricks and aliases matching this, best match first
+    pub query: Option<String>,
+
+    #[arg(long, value_enum, default_value_t)]
+    pub format: Format,
+
+    #[arg(short, long, value_hint=ValueHint::DirPath, value_terminator=",")]
+    pub brick_dirs: Option<Vec<PathBuf>>,
+}
+
+/// Search bricks by name, tags, description and files
+#[derive(Debug, Parser, Clone)]
+pub struct Search {
+    /// Text to search for, without it all bricks matching the filters are shown
+    pub query: Option<String>,
+
+    /// Only show bricks with this tag
+    #[arg(long = "tag")]
+    pub tags: Vec<String>,
+
+    /// Only show bricks that have a file with this name
+    #[arg(long = "has-file")]
+    pub has_files: Vec<String>,
+
+    /// Also search the content of the brick files
+    #[arg(long)]
+    pub full_text: bool,
+
+    #[arg(short, long, value_hint=ValueHint::DirPath, value_terminator=",")]
+    pub brick_dirs: Option<Vec<PathBuf>>,
+}
+
+/// Show everything a brick does before adding it
+#[derive(Debug, Parser, Clone)]
+pub struct Show {
+    /// Name of the brick to show
+    pub brick: String,
+
+    #[arg(long, value_enum, default_value_t)]
+    pub format: Format,
+
+    #[arg(short, long, value_hint=ValueHint::DirPath, value_terminator=",")]
+    pub brick_dirs: Option<Vec<PathBuf>>,
+}
+
+/// Create a new brick with a commented brick.toml
+#[derive(Debug, Parser, Clone)]
+pub struct New {
+    /// Name of the brick
+    pub name: String,
+
+    /// Brick directory to create the brick in, the first configured one by default
+    #[arg(long = "in", value_hint=ValueHint::DirPath)]
+    pub brick_dir: Option<PathBuf>,
+
+    /// Action the brick starts with
+    #[arg(short, long, value_enum, default_value_t)]
+    pub action: NewAction,
+}
+
+/// Actions a new brick can start with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+#[value(rename_all = "snake_case")]
+pub enum NewAction {
+    #[default]
+    InsertFile,
+    ModifyFile,
+    RunCommand,
+}
+
+/// Create a brick from files of the current project
+#[derive(Debug, Parser, Clone)]
+pub struct Capture {
+    /// Name of the brick
+    pub name: String,
+
+    /// Files or directories to copy into the brick, relative paths are kept
+    #[clap(required = true, num_args = 1..)]
+    pub files: Vec<PathBuf>,
+
+    /// Brick directory to create the brick in, the first configured one by default
+    #[arg(long = "in", value_hint=ValueHint::DirPath)]
+    pub brick_dir: Option<PathBuf>,
+
+    /// Replace a text in the files with a variable, e.g. `--var my_project=name`
+    #[arg(long = "var", value_name = "VALUE=NAME", value_parser = parse_replacement)]
+    pub replacements: Vec<(String, String)>,
+}
+
+/// Record changes made by hand and turn them into a brick
+#[derive(Debug, Parser, Clone)]
+pub struct Record {
+    #[command(subcommand)]
+    pub command: RecordCommand,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum RecordCommand {
+    /// Take a snapshot of the project in the current directory
+    Start,
+
+    /// Create a brick from the changes since the snapshot
+    Stop(RecordStop),
+}
+
+#[derive(Debug, Parser, Clone)]
+pub struct RecordStop {
+    /// Name of the brick
+    pub name: String,
+
+    /// Brick directory to create the brick in, the first configured one by default
+    #[arg(long = "in", value_hint=ValueHint::DirPath)]
+    pub brick_dir: Option<PathBuf>,
+}
+
+/// Check bricks for mistakes like unknown keys or missing files
+#[derive(Debug, Parser, Clone)]
+pub struct Validate {
+    /// Name or directory of the brick to check, the current directory by default
+    pub brick: Option<String>,
+
+    /// Check all bricks in the brick directories
+    #[arg(long, conflicts_with = "brick")]
+    pub all: bool,
+
+    #[arg(long, value_enum, default_value_t)]
+    pub format: Format,
+
     #[arg(short, long, value_hint=ValueHint::DirPath, value_terminator=",")]
     pub brick_dirs: Option<Vec<PathBuf>>,
 }
@@ -1,26 +1,60 @@
-use std::fs;
+use std::{collections::HashMap, fs, path::PathBuf};
 
-use colored::Colorize;
-use log::info;
+use colored::{Color, Colorize};
+use log::{error, info};
+use serde::Serialize;
 
 use crate::{
-    cmd::{List, Run},
-    config::{CraneConfig, map_aliases},
+    cmd::{Format, List, Run, load_bricks},
+    config::{Alias, CraneConfig, map_aliases},
+    fuzzy, output,
 };
-use crane_bricks::brick::bricks_in_dir;
+use crane_bricks::{
+    brick::{BrickInfo, check_crane_version_in, try_bricks_in_dir},
+    diagnostic::Diagnostic,
+};
+
+#[derive(Serialize)]
+struct Listed {
+    bricks: Vec<ListedBrick>,
+    aliases: Vec<ListedAlias>,
+}
+
+#[derive(Serialize)]
+struct ListedBrick {
+    #[serde(flatten)]
+    info: BrickInfo,
+    brick_dir: PathBuf,
+    /// Names of the aliases containing the brick
+    aliases: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct ListedAlias {
+    name: String,
+    bricks: Vec<String>,
+    /// Brick dir defining the alias, `None` for the config
+    source: Option<PathBuf>,
+}
 
 impl Run for List {
     fn run(&self) {
         let config = CraneConfig::new();
-        let brick_dirs = if let Some(brick_dirs) = &self.brick_dirs
-            && brick_dirs.is_empty()
-        {
-            brick_dirs
-        } else {
-            &config.brick_dirs().to_vec()
-        };
+        let brick_dirs = &config.brick_dirs_or(&self.brick_dirs);
 
-        let alias_mapped = map_aliases(config.alias());
+        let aliases = config.aliases(brick_dirs);
+        if self.format != Format::Text {
+            let listed = listed(self.query.as_deref(), brick_dirs, &aliases);
+            if let Err(error) = output::print_structured(self.format, &listed) {
+                error!("Could not print bricks: {}", error);
+            }
+            return;
+        }
+        if let Some(query) = &self.query {
+            list_matches(query, brick_dirs, &aliases);
+            return;
+        }
+        let alias_mapped = map_aliases(&aliases);
 
         for brick_dir in brick_dirs {
             println!(
@@ -30,14 +64,158 @@ impl Run for List {
                     .unwrap_or(brick_dir.to_path_buf())
                     .display()
             );
-            for brick in bricks_in_dir(brick_dir) {
+            let (bricks, failed) = try_bricks_in_dir(brick_dir);
+            for brick in bricks {
                 let mut affix = String::new();
+                if let Some(base) = brick.config().extends() {
+                    affix.push_str(&format!(" (extends '{}')", base));
+                }
                 if let Some(aliases) = alias_mapped.get(brick.name()) {
-                    affix = format!(" (aliased in '{}')", aliases.join("', '"));
+                    affix.push_str(&format!(" (aliased in '{}')", aliases.join("', '")));
+                }
+                let config = brick.config();
+                let version = config
+                    .version()
+                    .map(|version| format!(" {}", version))
+                    .unwrap_or_default();
+                info!("{}{}{}", brick.name(), version.purple(), affix.dimmed());
+                let mut details: Vec<String> = Vec::new();
+                if let Some(description) = config.description() {
+                    details.push(description.to_string());
+                }
+                if !config.tags().is_empty() {
+                    details.push(format!("tags: {}", config.tags().join(", ")));
+                }
+                if !config.authors().is_empty() {
+                    details.push(format!("by {}", config.authors().join(", ")));
+                }
+                if let Some(homepage) = config.homepage() {
+                    details.push(homepage.to_string());
+                }
+                if !details.is_empty() {
+                    println!("      {}", details.join(" • ").dimmed());
+                }
+                if let Err(error) = config.check_crane_version(env!("CARGO_PKG_VERSION"))
+                {
+                    println!("      {} {}", "⚠".red(), error);
                 }
-                info!("{}{}", brick.name(), affix.dimmed());
+            }
+            for (path, error) in failed {
+                let name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                // Bricks for a newer crane can use actions this one doesn't know
+                if let Err(error) =
+                    check_crane_version_in(&path, env!("CARGO_PKG_VERSION"))
+                {
+                    info!("{} {}", name, "needs a newer crane".red());
+                    println!("      {} {}", "⚠".red(), error);
+                    continue;
+                }
+                info!("{} {}", name, "invalid".red());
+                println!("      {} {}", "⚠".red(), error);
+                if let Some(diagnostic) = error.downcast_ref::<Diagnostic>() {
+                    for line in output::snippet(diagnostic, Color::Red) {
+                        println!("        {}", line);
+                    }
+                }
+                println!(
+                    "      {}",
+                    format!("Check it with `crane validate {}`", path.display()).dimmed()
+                );
             }
             println!()
         }
+
+        if aliases.is_empty() {
+            return;
+        }
+        println!("{} Found {} aliases", "→".green(), aliases.len());
+        for alias in &aliases {
+            info!(
+                "{} → {}{}",
+                alias.name(),
+                alias
+                    .bricks()
+                    .iter()
+                    .map(|entry| entry.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", "),
+                format!(" (from {})", alias.source_label()).dimmed()
+            );
+        }
+    }
+}
+
+/// List all bricks and aliases matching the query, best match first
+fn list_matches(query: &str, brick_dirs: &[PathBuf], aliases: &[Alias]) {
+    let mut labels: HashMap<String, String> = HashMap::new();
+    for brick_dir in brick_dirs {
+        for brick in load_bricks(brick_dir) {
+            labels
+                .entry(brick.name().to_string())
+                .or_insert(format!(" (in {})", brick_dir.display()));
+        }
+    }
+    for alias in aliases {
+        labels
+            .entry(alias.name().to_string())
+            .or_insert(format!(" (alias from {})", alias.source_label()));
+    }
+    let mut names: Vec<&str> = labels.keys().map(|name| name.as_str()).collect();
+    names.sort();
+    let matches = fuzzy::rank(query, names);
+    let plural = if matches.len() == 1 { "" } else { "es" };
+    println!(
+        "{} Found {} match{} for '{}'",
+        "→".green(),
+        matches.len().to_string().purple(),
+        plural,
+        query
+    );
+    for name in matches {
+        info!("{}{}", name, labels[name].dimmed());
+    }
+}
+
+/// All bricks and aliases for structured output, only the ones matching the
+/// query if there is one
+fn listed(query: Option<&str>, brick_dirs: &[PathBuf], aliases: &[Alias]) -> Listed {
+    let alias_mapped = map_aliases(aliases);
+    let mut bricks: Vec<ListedBrick> = brick_dirs
+        .iter()
+        .flat_map(|brick_dir| {
+            load_bricks(brick_dir)
+                .into_iter()
+                .map(|brick| ListedBrick {
+                    aliases: alias_mapped.get(brick.name()).cloned().unwrap_or_default(),
+                    info: brick.info(),
+                    brick_dir: brick_dir.clone(),
+                })
+                .collect::<Vec<ListedBrick>>()
+        })
+        .collect();
+    let mut aliases: Vec<ListedAlias> = aliases
+        .iter()
+        .map(|alias| ListedAlias {
+            name: alias.name().to_string(),
+            bricks: alias
+                .bricks()
+                .iter()
+                .map(|entry| entry.to_string())
+                .collect(),
+            source: alias.source().map(|source| source.to_path_buf()),
+        })
+        .collect();
+    if let Some(query) = query {
+        let brick_names: Vec<String> =
+            bricks.iter().map(|brick| brick.info.name.clone()).collect();
+        let ranked = fuzzy::rank(query, brick_names.iter().map(String::as_str));
+        bricks.retain(|brick| ranked.contains(&brick.info.name.as_str()));
+        bricks
+            .sort_by_key(|brick| ranked.iter().position(|name| *name == brick.info.name));
+        aliases.retain(|alias| fuzzy::score(query, &alias.name).is_some());
     }
+    Listed { bricks, aliases }
 }
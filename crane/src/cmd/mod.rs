@@ -1,6 +1,23 @@
 mod add;
+mod capture;
 mod commands;
 mod list;
+mod new;
+mod record;
+mod search;
+mod show;
+mod validate;
+
+use std::path::Path;
+
+use colored::{Color, Colorize};
+use crane_bricks::{
+    brick::{Brick, check_crane_version_in, try_bricks_in_dir},
+    diagnostic::Diagnostic,
+};
+use log::warn;
+
+use crate::output;
 
 pub use crate::cmd::commands::*;
 
@@ -13,6 +30,36 @@ impl Run for CraneCli {
         match &self.command {
             CraneCommand::Add(cmd) => cmd.run(),
             CraneCommand::List(cmd) => cmd.run(),
+            CraneCommand::Search(cmd) => cmd.run(),
+            CraneCommand::Show(cmd) => cmd.run(),
+            CraneCommand::New(cmd) => cmd.run(),
+            CraneCommand::Capture(cmd) => cmd.run(),
+            CraneCommand::Record(cmd) => cmd.run(),
+            CraneCommand::Validate(cmd) => cmd.run(),
+        }
+    }
+}
+
+/// The bricks in a brick dir. Bricks that can't be loaded are skipped with a
+/// warning that points to `crane validate`.
+fn load_bricks(brick_dir: &Path) -> Vec<Brick> {
+    let (bricks, failed) = try_bricks_in_dir(brick_dir);
+    for (path, error) in failed {
+        // Bricks for a newer crane can use actions this one doesn't know
+        if let Err(error) = check_crane_version_in(&path, env!("CARGO_PKG_VERSION")) {
+            warn!("Skipping brick at '{}': {}", path.display(), error);
+            continue;
+        }
+        warn!("Skipping brick at '{}': {}", path.display(), error);
+        if let Some(diagnostic) = error.downcast_ref::<Diagnostic>() {
+            for line in output::snippet(diagnostic, Color::Yellow) {
+                eprintln!("      {}", line);
+            }
         }
+        eprintln!(
+            "      {}",
+            format!("Check it with `crane validate {}`", path.display()).dimmed()
+        );
     }
+    bricks
 }
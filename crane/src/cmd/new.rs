@@ -0,0 +1,190 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::anyhow;
+use colored::Colorize;
+use log::{error, info};
+
+use crate::{
+    cmd::{New, NewAction, Run},
+    config::CraneConfig,
+};
+use crane_bricks::{
+    actions::{
+        Action, common::Common, insert_file::InsertFileAction,
+        modify_file::ModifyFileAction, run_command::RunCommandAction,
+    },
+    brick::{BRICK_CONFIG_FILE, BrickConfig, bricks_in_dir},
+    resolve::find_brick,
+};
+
+impl Run for New {
+    fn run(&self) {
+        let config = CraneConfig::new();
+        let Some(brick_dir) = self
+            .brick_dir
+            .as_ref()
+            .or_else(|| config.brick_dirs().first())
+        else {
+            error!("No brick directory is configured, choose one with --in <dir>");
+            return;
+        };
+        match self.create(brick_dir) {
+            Ok(path) => {
+                println!(
+                    "{} Created brick '{}' at {}",
+                    "→".green(),
+                    self.name,
+                    path.display()
+                );
+                if self.action == NewAction::InsertFile {
+                    info!("Put the files the brick inserts into its directory");
+                }
+                info!("Edit {} to change what the brick does", BRICK_CONFIG_FILE);
+            }
+            Err(error) => error!("{}", error),
+        }
+    }
+}
+
+impl New {
+    /// Create the brick directory with its config, returns the config path
+    fn create(&self, brick_dir: &Path) -> anyhow::Result<PathBuf> {
+        let path = new_brick_dir(brick_dir, &self.name)?;
+        let name = self.name.trim();
+        let config = BrickConfig::new(name.to_string(), vec![self.action.action(name)]);
+        let content = brick_toml(&config)?;
+        fs::create_dir_all(&path)?;
+        let config_path = path.join(BRICK_CONFIG_FILE);
+        fs::write(&config_path, content)?;
+        Ok(config_path)
+    }
+}
+
+/// Path of a new brick in the brick dir, fails if the name is invalid or
+/// already taken
+pub(super) fn new_brick_dir(brick_dir: &Path, name: &str) -> anyhow::Result<PathBuf> {
+    let name = name.trim();
+    if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\']) {
+        return Err(anyhow!("'{}' is not a valid brick name", name));
+    }
+    let path = brick_dir.join(name);
+    if path.exists() {
+        return Err(anyhow!("'{}' already exists", path.display()));
+    }
+    if find_brick(&bricks_in_dir(brick_dir), name).is_some() {
+        return Err(anyhow!(
+            "A brick named '{}' already exists in {}",
+            name,
+            brick_dir.display()
+        ));
+    }
+    Ok(path)
+}
+
+impl NewAction {
+    /// An example of the action, which works as it is
+    fn action(&self, name: &str) -> Action {
+        match self {
+            NewAction::InsertFile => Action::InsertFile(InsertFileAction::default()),
+            NewAction::ModifyFile => {
+                let mut action = ModifyFileAction::new(
+                    String::from("[dependencies]"),
+                    Some(String::from("\nserde = '1'")),
+                );
+                action.common.sources = vec![String::from("Cargo.toml")];
+                Action::ModifyFile(action)
+            }
+            NewAction::RunCommand => Action::RunCommand(RunCommandAction {
+                common: Common::default(),
+                command: format!("echo 'Added {}'", name),
+            }),
+        }
+    }
+}
+
+/// Serialize the config, with comments explaining the optional keys
+pub(super) fn brick_toml(config: &BrickConfig) -> anyhow::Result<String> {
+    let serialized = toml::to_string(config)?;
+    let mut lines: Vec<String> = vec![
+        String::from(
+            "# Brick config, see the \"Brick Config\" chapter of the crane book",
+        ),
+        String::new(),
+    ];
+    let mut named = false;
+    for line in serialized.lines() {
+        lines.push(line.to_string());
+        if line.starts_with("name = ") && !named {
+            named = true;
+            lines.extend(
+                [
+                    "# description = \"What the brick is for\"",
+                    "# tags = []",
+                    "# authors = []",
+                    "# version = \"0.1.0\"",
+                    "",
+                    "# Bricks that are added before this one",
+                    "# requires = []",
+                    "",
+                    "# Variables that can be used in files and actions as {{ name }}",
+                    "# [[variables]]",
+                    "# name = \"author\"",
+                    "# default = \"ACME\"",
+                ]
+                .map(str::to_string),
+            );
+        }
+        if let Some(action) = line.strip_prefix("action = ") {
+            let hints = action_hints(action.trim_matches('"'));
+            lines.extend(hints.iter().map(|hint| hint.to_string()));
+        }
+    }
+    Ok(lines.join("\n") + "\n")
+}
+
+fn action_hints(action: &str) -> &'static [&'static str] {
+    match action {
+        "insert_file" => &[
+            "# Inserts the files in the brick directory into the project",
+            "# sources = [\"LICENSE\"] # only these files",
+            "# if_file_exists = \"append\" # or \"replace\", \"pass\"",
+            "# destination = \"docs/\"",
+            "# when = { file_exists = \"Cargo.toml\" } # only if the condition is met",
+        ],
+        "modify_file" => &[
+            "# Inserts the content next to the selector in the source files",
+            "# type can be \"append\", \"prepend\" or \"replace\"",
+            "# Selectors starting with \"re:\" are regular expressions",
+            "# when = { file_exists = \"Cargo.toml\" } # only if the condition is met",
+        ],
+        "run_command" => &[
+            "# Runs the command in the project directory",
+            "# working_dir = \"./src/\"",
+            "# when = { command_exists = \"cargo\" } # only if the condition is met",
+        ],
+        _ => &[],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_brick_toml() {
+        for action in [
+            NewAction::InsertFile,
+            NewAction::ModifyFile,
+            NewAction::RunCommand,
+        ] {
+            let config =
+                BrickConfig::new(String::from("test"), vec![action.action("test")]);
+            let content = brick_toml(&config).unwrap();
+            assert!(content.contains("# description"));
+            assert_eq!(toml::from_str::<BrickConfig>(&content).unwrap(), config);
+        }
+    }
+}
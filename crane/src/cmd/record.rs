@@ -0,0 +1,460 @@
+use std::{env, fs, path::Path};
+
+use anyhow::anyhow;
+use colored::Colorize;
+use log::{error, info};
+use similar::{DiffTag, TextDiff};
+
+use crate::{
+    cmd::{
+        Record, RecordCommand, RecordStop, Run,
+        new::{brick_toml, new_brick_dir},
+    },
+    config::CraneConfig,
+    diff,
+    snapshot::{self, project_files, snapshot_dir},
+};
+use crane_bricks::{
+    actions::{
+        Action,
+        common::Common,
+        delete_file::DeleteFileAction,
+        insert_file::{FileExistsAction, InsertFileAction},
+        modify_file::{ModifyFileAction, ModifyType},
+        patch_file::PatchFileAction,
+    },
+    brick::{BRICK_CONFIG_FILE, BrickConfig},
+    file_utils::is_binary,
+};
+
+/// Most lines around a change that are added to make its selector unique
+const MAX_CONTEXT: usize = 3;
+
+/// A file that changed since the snapshot
+enum Recorded {
+    Added(String),
+    Modified { name: String, how: String },
+    Removed(String),
+}
+
+impl Run for Record {
+    fn run(&self) {
+        match &self.command {
+            RecordCommand::Start => start(),
+            RecordCommand::Stop(stop) => stop.run(),
+        }
+    }
+}
+
+fn start() {
+    let Ok(cwd) = env::current_dir() else {
+        error!("Could not read the current directory");
+        return;
+    };
+    if snapshot_dir(&cwd).exists() {
+        info!("Replacing the snapshot of the previous recording");
+    }
+    match snapshot::take(&cwd) {
+        Ok(count) => {
+            println!(
+                "{} Took a snapshot of {} files",
+                "→".green(),
+                count.to_string().purple()
+            );
+            info!("Make your changes, then run `crane record stop <name>`");
+        }
+        Err(error) => error!("Could not take a snapshot: {}", error),
+    }
+}
+
+impl Run for RecordStop {
+    fn run(&self) {
+        let Ok(cwd) = env::current_dir() else {
+            error!("Could not read the current directory");
+            return;
+        };
+        let snapshot = snapshot_dir(&cwd);
+        if !snapshot.exists() {
+            error!("Nothing is recorded, start with `crane record start`");
+            return;
+        }
+        let config = CraneConfig::new();
+        let Some(brick_dir) = self
+            .brick_dir
+            .as_ref()
+            .or_else(|| config.brick_dirs().first())
+        else {
+            error!("No brick directory is configured, choose one with --in <dir>");
+            return;
+        };
+        let path = match new_brick_dir(brick_dir, &self.name) {
+            Ok(path) => path,
+            Err(error) => {
+                error!("{}", error);
+                return;
+            }
+        };
+        let recorded = match self.record(&cwd, &snapshot, &path) {
+            Ok(recorded) => recorded,
+            Err(error) => {
+                // Don't leave a half recorded brick behind
+                let _ = fs::remove_dir_all(&path);
+                error!("Could not record brick: {}", error);
+                return;
+            }
+        };
+        if let Err(error) = fs::remove_dir_all(&snapshot) {
+            error!("Could not remove the snapshot: {}", error);
+        }
+
+        let plural = if recorded.len() == 1 { "" } else { "s" };
+        println!(
+            "{} Recorded {} change{} into brick '{}' at {}",
+            "→".green(),
+            recorded.len().to_string().purple(),
+            plural,
+            self.name.trim(),
+            path.display()
+        );
+        for change in &recorded {
+            match change {
+                Recorded::Added(name) => info!("Added {}", name),
+                Recorded::Modified { name, how } => {
+                    info!("Modified {}{}", name, format!(" ({})", how).dimmed())
+                }
+                Recorded::Removed(name) => info!("Removed {}", name),
+            }
+        }
+    }
+}
+
+impl RecordStop {
+    /// Write a brick to `path` that repeats the changes made to the project
+    /// since the snapshot
+    fn record(
+        &self,
+        cwd: &Path,
+        snapshot: &Path,
+        path: &Path,
+    ) -> anyhow::Result<Vec<Recorded>> {
+        let before = project_files(snapshot)?;
+        let after = project_files(cwd)?;
+
+        let mut recorded: Vec<Recorded> = Vec::new();
+        let mut added: Vec<String> = Vec::new();
+        // Files that are replaced as a whole, as they can't be modified
+        let mut replaced: Vec<String> = Vec::new();
+        let mut edits: Vec<Action> = Vec::new();
+        for (name, current) in &after {
+            let Some(original) = before.get(name) else {
+                added.push(name.clone());
+                recorded.push(Recorded::Added(name.clone()));
+                continue;
+            };
+            let (original, content) = (fs::read(original)?, fs::read(current)?);
+            if original == content {
+                continue;
+            }
+            let texts = String::from_utf8(original)
+                .ok()
+                .zip(String::from_utf8(content).ok())
+                .filter(|(original, content)| {
+                    !is_binary(original.as_bytes()) && !is_binary(content.as_bytes())
+                });
+            let how =
+                match texts.map(|(original, content)| edit(name, &original, &content)) {
+                    Some(Some(actions)) => {
+                        let how = match actions.as_slice() {
+                            [Action::PatchFile(_)] => String::from("patch"),
+                            [_] => String::from("1 modification"),
+                            actions => format!("{} modifications", actions.len()),
+                        };
+                        edits.extend(actions);
+                        how
+                    }
+                    _ => {
+                        replaced.push(name.clone());
+                        String::from("replaced")
+                    }
+                };
+            recorded.push(Recorded::Modified {
+                name: name.clone(),
+                how,
+            });
+        }
+        let removed: Vec<String> = before
+            .keys()
+            .filter(|name| !after.contains_key(*name))
+            .cloned()
+            .collect();
+        recorded.extend(removed.iter().cloned().map(Recorded::Removed));
+        if recorded.is_empty() {
+            return Err(anyhow!("Nothing changed since `crane record start`"));
+        }
+
+        fs::create_dir_all(path)?;
+        for name in added.iter().chain(&replaced) {
+            if name == BRICK_CONFIG_FILE {
+                return Err(anyhow!(
+                    "'{}' can't be recorded, bricks use it for their config",
+                    name
+                ));
+            }
+            let target = path.join(name);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(&after[name], &target)?;
+        }
+
+        let mut actions: Vec<Action> = Vec::new();
+        if !added.is_empty() {
+            actions.push(Action::InsertFile(InsertFileAction {
+                common: sources(added),
+                ..Default::default()
+            }));
+        }
+        if !replaced.is_empty() {
+            actions.push(Action::InsertFile(InsertFileAction {
+                common: sources(replaced),
+                if_file_exists: FileExistsAction::Replace,
+                ..Default::default()
+            }));
+        }
+        actions.extend(edits);
+        if !removed.is_empty() {
+            actions.push(Action::DeleteFile(DeleteFileAction {
+                common: sources(removed),
+            }));
+        }
+        let config = BrickConfig::new(self.name.trim().to_string(), actions);
+        fs::write(path.join(BRICK_CONFIG_FILE), brick_toml(&config)?)?;
+        Ok(recorded)
+    }
+}
+
+fn sources(names: Vec<String>) -> Common {
+    Common {
+        sources: names,
+        ..Default::default()
+    }
+}
+
+/// Actions that turn `original` into `content`: modifications if every change
+/// has a unique selector, otherwise a patch. `None` if neither works.
+fn edit(name: &str, original: &str, content: &str) -> Option<Vec<Action>> {
+    if let Some(modifications) = modifications(original, content) {
+        return Some(
+            modifications
+                .into_iter()
+                .map(|mut modification| {
+                    modification.common.sources = vec![name.to_string()];
+                    modification.only_sources = true;
+                    Action::ModifyFile(modification)
+                })
+                .collect(),
+        );
+    }
+    let patch = PatchFileAction {
+        common: Common::default(),
+        path: name.to_string(),
+        patch: diff::unified_hunks(original, content),
+    };
+    let applies = patch
+        .apply(original, &patch.patch)
+        .is_ok_and(|patched| patched == content);
+    applies.then(|| vec![Action::PatchFile(patch)])
+}
+
+/// One modification per changed block of lines. The selector of each is the
+/// changed lines, with as few surrounding lines as needed to make it unique
+/// at the time it is applied.
+fn modifications(original: &str, content: &str) -> Option<Vec<ModifyFileAction>> {
+    let old_lines: Vec<&str> = original.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let mut current = original.to_string();
+    let mut modifications = Vec::new();
+    for op in TextDiff::from_lines(original, content).ops() {
+        if op.tag() == DiffTag::Equal {
+            continue;
+        }
+        let (old, new) = (op.old_range(), op.new_range());
+        let inserted = new_lines[new].concat();
+        let contexts = (0..=MAX_CONTEXT)
+            .flat_map(|context| [(context, 0), (0, context), (context, context)]);
+        let (modification, replacement, selector) =
+            contexts.into_iter().find_map(|(before, after)| {
+                let start = old.start.checked_sub(before)?;
+                let end = old.end + after;
+                if end > old_lines.len() {
+                    return None;
+                }
+                let selector = old_lines[start..end].concat();
+                // Selectors starting with `re:` would be read as a regex
+                if selector.is_empty()
+                    || selector.starts_with("re:")
+                    || current.matches(&selector).count() != 1
+                {
+                    return None;
+                }
+                let leading = old_lines[start..old.start].concat();
+                let trailing = old_lines[old.end..end].concat();
+                let (r#type, modified) = if old.is_empty() && after == 0 {
+                    (ModifyType::Append, inserted.clone())
+                } else if old.is_empty() && before == 0 {
+                    (ModifyType::Prepend, inserted.clone())
+                } else {
+                    (
+                        ModifyType::Replace,
+                        format!("{}{}{}", leading, inserted, trailing),
+                    )
+                };
+                let replacement = match r#type {
+                    ModifyType::Append => format!("{}{}", selector, modified),
+                    ModifyType::Prepend => format!("{}{}", modified, selector),
+                    ModifyType::Replace => modified.clone(),
+                };
+                let modification =
+                    ModifyFileAction::new(selector.clone(), Some(modified))
+                        .with_type(r#type);
+                Some((modification, replacement, selector))
+            })?;
+        current = current.replacen(&selector, &replacement, 1);
+        modifications.push(modification);
+    }
+    (current == content).then_some(modifications)
+}
+
+#[cfg(test)]
+mod tests {
+    use crane_bricks::{brick::Brick, context::ActionContext};
+
+    use super::*;
+
+    /// Apply the modifications like the modify_file action would
+    fn apply(original: &str, modifications: &[ModifyFileAction]) -> String {
+        modifications
+            .iter()
+            .fold(original.to_string(), |text, modification| {
+                modification
+                    .modify_content(text, &modification.content())
+                    .unwrap()
+            })
+    }
+
+    #[test]
+    fn test_modifications() {
+        let original = "[package]\nname = \"demo\"\n\n[dependencies]\n\n[features]\n";
+        let content = "[package]\nname = \"demo\"\nedition = \"2024\"\n\n[dependencies]\nserde = \"1\"\n\n";
+        let modified = modifications(original, content).unwrap();
+        assert_eq!(apply(original, &modified), content);
+
+        // Repeated lines need context to be unique
+        let original = "a\nx\nb\nx\n";
+        let content = "a\nx\nb\nx\ny\n";
+        let modified = modifications(original, content).unwrap();
+        assert_eq!(modified[0].selector, "b\nx\n");
+        assert_eq!(apply(original, &modified), content);
+
+        // Lines starting with `re:` can't be selectors, the line before can
+        let original = "a\nre:x\nb\n";
+        let content = "a\nre:y\nb\n";
+        let modified = modifications(original, content).unwrap();
+        assert_eq!(modified[0].selector, "a\nre:x\n");
+        assert_eq!(apply(original, &modified), content);
+        assert!(matches!(
+            edit("f", "re:x\nb\n", "re:y\nb\n").unwrap().as_slice(),
+            [Action::PatchFile(_)]
+        ));
+
+        // Nothing is unique in a file of equal lines
+        assert!(
+            modifications(
+                "x\nx\nx\nx\nx\nx\nx\nx\nx\n",
+                "x\nx\nx\nx\ny\nx\nx\nx\nx\nx\n"
+            )
+            .is_none()
+        );
+        assert!(matches!(
+            edit(
+                "f",
+                "x\nx\nx\nx\nx\nx\nx\nx\nx\n",
+                "x\nx\nx\nx\ny\nx\nx\nx\nx\nx\n"
+            )
+            .unwrap()
+            .as_slice(),
+            [Action::PatchFile(_)]
+        ));
+    }
+
+    #[test]
+    fn test_record() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let project = tmpdir.path().join("project");
+        fs::create_dir_all(project.join("src")).unwrap();
+        fs::create_dir_all(project.join("target")).unwrap();
+        fs::write(project.join("Cargo.toml"), "[package]\nname = \"demo\"\n").unwrap();
+        fs::write(project.join("src/main.rs"), "fn main() {}\n").unwrap();
+        fs::write(project.join("logo.png"), b"\x89PNG\0\x01").unwrap();
+        fs::write(project.join("old.txt"), "old\n").unwrap();
+        fs::write(project.join("target/build.log"), "ignored\n").unwrap();
+        assert_eq!(snapshot::take(&project).unwrap(), 4);
+        let original = tmpdir.path().join("original");
+        for (name, path) in project_files(&snapshot_dir(&project)).unwrap() {
+            fs::create_dir_all(original.join(&name).parent().unwrap()).unwrap();
+            fs::copy(path, original.join(&name)).unwrap();
+        }
+
+        fs::write(
+            project.join("Cargo.toml"),
+            "[package]\nname = \"demo\"\nedition = \"2024\"\n",
+        )
+        .unwrap();
+        fs::write(project.join("src/lib.rs"), "pub fn lib() {}\n").unwrap();
+        fs::write(project.join("logo.png"), b"\x89PNG\0\x02").unwrap();
+        fs::remove_file(project.join("old.txt")).unwrap();
+        fs::write(project.join("target/build.log"), "changed\n").unwrap();
+
+        let stop = RecordStop {
+            name: String::from("recorded"),
+            brick_dir: None,
+        };
+        let path = tmpdir.path().join("bricks/recorded");
+        let recorded = stop
+            .record(&project, &snapshot_dir(&project), &path)
+            .unwrap();
+        let changes: Vec<String> = recorded
+            .iter()
+            .map(|change| match change {
+                Recorded::Added(name) => format!("+ {}", name),
+                Recorded::Modified { name, how } => format!("~ {} ({})", name, how),
+                Recorded::Removed(name) => format!("- {}", name),
+            })
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                "~ Cargo.toml (1 modification)",
+                "~ logo.png (replaced)",
+                "+ src/lib.rs",
+                "- old.txt",
+            ]
+        );
+        assert!(path.join("src/lib.rs").exists());
+        assert!(!path.join("Cargo.toml").exists());
+
+        // Adding the brick to the original project repeats the changes
+        let brick = Brick::try_from(path).unwrap();
+        brick
+            .execute(&ActionContext::new(false), &original)
+            .unwrap();
+        let files = |dir: &Path| -> Vec<(String, Vec<u8>)> {
+            project_files(dir)
+                .unwrap()
+                .into_iter()
+                .map(|(name, path)| (name, fs::read(path).unwrap()))
+                .collect()
+        };
+        assert_eq!(files(&original), files(&project));
+    }
+}
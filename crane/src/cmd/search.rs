@@ -0,0 +1,256 @@
+use std::path::{Path, PathBuf};
+
+use colored::Colorize;
+use log::info;
+
+use crate::{
+    cmd::{Run, Search, load_bricks},
+    config::CraneConfig,
+    fuzzy,
+};
+use crane_bricks::brick::Brick;
+
+/// Score for a tag that is equal to the query
+const TAG_SCORE: i64 = 100;
+
+/// Score for a tag or description containing the query
+const CONTAINS_SCORE: i64 = 50;
+
+/// Score for every brick file containing the query in full-text mode
+const FILE_SCORE: i64 = 10;
+
+/// How many matching lines are shown per brick in full-text mode
+const SHOWN_LINES: usize = 3;
+
+struct SearchResult {
+    brick: Brick,
+    brick_dir: PathBuf,
+    score: i64,
+
+    /// Lines of brick files matching the query, as `file:line: text`
+    lines: Vec<String>,
+}
+
+impl Run for Search {
+    fn run(&self) {
+        let config = CraneConfig::new();
+        let results = self.results(&config.brick_dirs_or(&self.brick_dirs));
+
+        let plural = if results.len() == 1 { "" } else { "s" };
+        println!(
+            "{} Found {} brick{}",
+            "→".green(),
+            results.len().to_string().purple(),
+            plural
+        );
+        for result in &results {
+            info!(
+                "{}{}",
+                result.brick.name(),
+                format!(" (in {})", result.brick_dir.display()).dimmed()
+            );
+            if let Some(description) = result.brick.config().description() {
+                println!("      {}", description.dimmed());
+            }
+            for line in &result.lines {
+                println!("      {} {}", "•".dimmed(), line);
+            }
+        }
+    }
+}
+
+impl Search {
+    /// The bricks in the brick dirs matching the search, best match first
+    fn results(&self, brick_dirs: &[PathBuf]) -> Vec<SearchResult> {
+        let mut results: Vec<SearchResult> = Vec::new();
+        for brick_dir in brick_dirs {
+            for brick in load_bricks(brick_dir) {
+                if !self.matches_filters(&brick) {
+                    continue;
+                }
+                if let Some(result) = self.score(brick, brick_dir) {
+                    results.push(result);
+                }
+            }
+        }
+        results.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then_with(|| a.brick.name().cmp(b.brick.name()))
+        });
+        results
+    }
+
+    /// Check the `--tag` and `--has-file` filters
+    fn matches_filters(&self, brick: &Brick) -> bool {
+        let tags = brick.config().tags();
+        let has_tags = self.tags.iter().all(|tag| {
+            tags.iter()
+                .any(|other| other.to_lowercase() == tag.to_lowercase())
+        });
+        if !has_tags {
+            return false;
+        }
+        if self.has_files.is_empty() {
+            return true;
+        }
+        let files = brick.all_files();
+        self.has_files
+            .iter()
+            .all(|name| files.iter().any(|file| file.name() == name))
+    }
+
+    /// Rank the brick by how well it matches the query, `None` if it doesn't
+    fn score(&self, brick: Brick, brick_dir: &Path) -> Option<SearchResult> {
+        let mut result = SearchResult {
+            brick,
+            brick_dir: brick_dir.to_path_buf(),
+            score: 0,
+            lines: Vec::new(),
+        };
+        let Some(query) = &self.query else {
+            return Some(result);
+        };
+        let query = query.to_lowercase();
+        let config = result.brick.config();
+
+        result.score += fuzzy::score(&query, config.name()).unwrap_or(0) * 2;
+        for tag in config.tags() {
+            let tag = tag.to_lowercase();
+            if tag == query {
+                result.score += TAG_SCORE;
+            } else if tag.contains(&query) {
+                result.score += CONTAINS_SCORE;
+            }
+        }
+        if let Some(description) = config.description()
+            && description.to_lowercase().contains(&query)
+        {
+            result.score += CONTAINS_SCORE;
+        }
+
+        if self.full_text {
+            for file in result.brick.all_files() {
+                let Some(text) = file.text() else {
+                    continue;
+                };
+                let mut found = false;
+                for (index, line) in text.lines().enumerate() {
+                    if !line.to_lowercase().contains(&query) {
+                        continue;
+                    }
+                    found = true;
+                    if result.lines.len() < SHOWN_LINES {
+                        result.lines.push(format!(
+                            "{}:{}: {}",
+                            file.name(),
+                            index + 1,
+                            line.trim()
+                        ));
+                    }
+                }
+                if found {
+                    result.score += FILE_SCORE;
+                }
+            }
+        }
+
+        if result.score > 0 { Some(result) } else { None }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    /// Create a brick in `brick_dir` with the config and files
+    fn add_brick(brick_dir: &Path, name: &str, config: &str, files: &[(&str, &str)]) {
+        let dir = brick_dir.join(name);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("brick.toml"),
+            format!("name = \"{}\"\n{}", name, config),
+        )
+        .unwrap();
+        for (file, content) in files {
+            fs::write(dir.join(file), content).unwrap();
+        }
+    }
+
+    fn search(query: Option<&str>) -> Search {
+        Search {
+            query: query.map(String::from),
+            tags: Vec::new(),
+            has_files: Vec::new(),
+            full_text: false,
+            brick_dirs: None,
+        }
+    }
+
+    /// Names of the found bricks, best match first
+    fn found(search: &Search, brick_dir: &Path) -> Vec<String> {
+        search
+            .results(&[brick_dir.to_path_buf()])
+            .iter()
+            .map(|result| result.brick.name().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_search() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let brick_dir = tmpdir.path();
+        add_brick(
+            brick_dir,
+            "rustfmt",
+            "tags = [\"rust\", \"format\"]\ndescription = \"Formatting config\"",
+            &[("rustfmt.toml", "max_width = 90\n")],
+        );
+        add_brick(brick_dir, "serde", "tags = [\"Rust\"]", &[]);
+        add_brick(
+            brick_dir,
+            "license",
+            "tags = [\"legal\"]\ndescription = \"MIT license for rust projects\"",
+            &[("LICENSE", "MIT License\n")],
+        );
+        add_brick(
+            brick_dir,
+            "clippy",
+            "description = \"Lints\"",
+            &[("clippy.toml", "# Stricter rust lints\nmsrv = \"1.85\"\n")],
+        );
+
+        // Name, then tag, then description
+        assert_eq!(
+            found(&search(Some("rust")), brick_dir),
+            ["rustfmt", "serde", "license"]
+        );
+        assert_eq!(found(&search(Some("legal")), brick_dir), ["license"]);
+        assert_eq!(found(&search(Some("form")), brick_dir), ["rustfmt"]);
+        assert!(found(&search(Some("unknown")), brick_dir).is_empty());
+
+        // Filters without a query show all matching bricks by name
+        let mut tagged = search(None);
+        tagged.tags = vec![String::from("RUST")];
+        assert_eq!(found(&tagged, brick_dir), ["rustfmt", "serde"]);
+        tagged.query = Some(String::from("serde"));
+        assert_eq!(found(&tagged, brick_dir), ["serde"]);
+
+        let mut with_file = search(None);
+        with_file.has_files = vec![String::from("LICENSE")];
+        assert_eq!(found(&with_file, brick_dir), ["license"]);
+        with_file.tags = vec![String::from("rust")];
+        assert!(found(&with_file, brick_dir).is_empty());
+
+        // Full text also finds the content of brick files, with the lines
+        let mut full_text = search(Some("rust"));
+        full_text.full_text = true;
+        let results = full_text.results(&[brick_dir.to_path_buf()]);
+        let names: Vec<&str> = results.iter().map(|result| result.brick.name()).collect();
+        assert_eq!(names, ["rustfmt", "serde", "license", "clippy"]);
+        assert_eq!(results[3].lines, ["clippy.toml:1: # Stricter rust lints"]);
+        assert_eq!(results[3].score, FILE_SCORE);
+    }
+}
@@ -0,0 +1,301 @@
+use std::path::{Path, PathBuf};
+
+use colored::Colorize;
+use log::{error, info};
+use serde::Serialize;
+
+use crate::{
+    cmd::{Format, Run, Show, load_bricks},
+    config::{CraneConfig, map_aliases},
+    fuzzy, markdown, output,
+};
+use crane_bricks::{
+    actions::Action,
+    brick::{Brick, BrickFile, BrickInfo, BrickPart},
+    resolve::find_brick,
+};
+
+#[derive(Serialize)]
+struct Shown {
+    #[serde(flatten)]
+    info: BrickInfo,
+    brick_dir: PathBuf,
+    /// Source paths of the bricks it extends, the root base first
+    base_paths: Vec<PathBuf>,
+    variables: Vec<ShownVariable>,
+    actions: Vec<ShownAction>,
+    files: Vec<ShownFile>,
+    /// Names of the aliases containing the brick
+    aliases: Vec<String>,
+    readme: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ShownVariable {
+    name: String,
+    default: Option<String>,
+    description: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ShownAction {
+    action: String,
+    description: String,
+    /// Variant or feature the action belongs to, `None` for the brick itself
+    part: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ShownFile {
+    name: String,
+    size: usize,
+    /// Variant or feature the file belongs to, `None` for the brick itself
+    part: Option<String>,
+}
+
+impl Run for Show {
+    fn run(&self) {
+        let config = CraneConfig::new();
+        let brick_dirs = &config.brick_dirs_or(&self.brick_dirs);
+
+        let bricks: Vec<Brick> =
+            brick_dirs.iter().flat_map(|dir| load_bricks(dir)).collect();
+        let Some(brick) = find_brick(&bricks, &self.brick) else {
+            let suggestions =
+                fuzzy::suggestions(&self.brick, bricks.iter().map(|brick| brick.name()));
+            if suggestions.is_empty() {
+                error!("Could not find brick '{}'", self.brick);
+            } else {
+                error!(
+                    "Could not find brick '{}', did you mean '{}'?",
+                    self.brick,
+                    suggestions.join("', '")
+                );
+            }
+            return;
+        };
+        let brick_dir = brick
+            .path()
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+
+        let aliases = map_aliases(&config.aliases(brick_dirs))
+            .remove(brick.name())
+            .unwrap_or_default();
+        let shown = shown(brick, brick_dir, aliases);
+        if self.format != Format::Text {
+            if let Err(error) = output::print_structured(self.format, &shown) {
+                error!("Could not print brick: {}", error);
+            }
+            return;
+        }
+        print_shown(brick, &shown);
+    }
+}
+
+/// Everything there is to know about a brick before adding it
+fn shown(brick: &Brick, brick_dir: PathBuf, aliases: Vec<String>) -> Shown {
+    let config = brick.config();
+    let parts: Vec<&BrickPart> =
+        config.variants().iter().chain(config.features()).collect();
+    let part_of = |file: &BrickFile| {
+        parts
+            .iter()
+            .find(|part| part.files().iter().any(|name| name == file.name()))
+            .map(|part| part.name().to_string())
+    };
+
+    let own_actions = config.actions().iter().map(|action| (action, None));
+    let part_actions = parts.iter().flat_map(|part| {
+        part.actions()
+            .iter()
+            .map(|action| (action, Some(part.name().to_string())))
+    });
+    let actions = own_actions
+        .chain(part_actions)
+        .map(|(action, part): (&Action, Option<String>)| ShownAction {
+            action: action.name().to_string(),
+            description: action.to_string(),
+            part,
+        })
+        .collect();
+
+    let files = brick.all_files();
+    let readme = files
+        .iter()
+        .find(|file| file.name().to_lowercase().starts_with("readme"))
+        .and_then(|file| file.text())
+        .map(str::to_string);
+
+    Shown {
+        info: brick.info(),
+        brick_dir,
+        base_paths: brick.base_paths().to_vec(),
+        variables: config
+            .variables()
+            .iter()
+            .map(|variable| ShownVariable {
+                name: variable.name().to_string(),
+                default: variable.default().map(str::to_string),
+                description: variable.description().map(str::to_string),
+            })
+            .collect(),
+        actions,
+        files: files
+            .iter()
+            .map(|file| ShownFile {
+                name: file.name().to_string(),
+                size: file.content().len(),
+                part: part_of(file),
+            })
+            .collect(),
+        aliases,
+        readme,
+    }
+}
+
+fn print_shown(brick: &Brick, shown: &Shown) {
+    let config = brick.config();
+    let version = config
+        .version()
+        .map(|version| format!(" {}", version))
+        .unwrap_or_default();
+    println!(
+        "{} {}{}",
+        "→".green(),
+        brick.name().bold(),
+        version.purple()
+    );
+    if let Some(description) = config.description() {
+        println!("  {}", description);
+    }
+    println!();
+
+    info!("Path: {}", brick.path().display());
+    for base_path in shown.base_paths.iter().rev() {
+        info!("Extends: {}", base_path.display());
+    }
+    let lists = [
+        ("Tags", config.tags()),
+        ("Authors", config.authors()),
+        ("Requires", config.requires()),
+        ("Conflicts with", config.conflicts()),
+        ("Provides", config.provides()),
+    ];
+    for (label, values) in lists {
+        if !values.is_empty() {
+            info!("{}: {}", label, values.join(", "));
+        }
+    }
+    if let Some(homepage) = config.homepage() {
+        info!("Homepage: {}", homepage);
+    }
+    if let Some(min_crane_version) = config.min_crane_version() {
+        info!("Needs crane {} or newer", min_crane_version);
+        if let Err(error) = config.check_crane_version(env!("CARGO_PKG_VERSION")) {
+            println!("  {} {}", "⚠".red(), error);
+        }
+    }
+    if !shown.aliases.is_empty() {
+        info!("Aliased in '{}'", shown.aliases.join("', '"));
+    }
+
+    if !shown.variables.is_empty() {
+        println!("\n{} Variables", "→".green());
+        for variable in &shown.variables {
+            let default = variable
+                .default
+                .as_ref()
+                .map(|default| format!(" = {:?}", default))
+                .unwrap_or_default();
+            let description = variable
+                .description
+                .as_ref()
+                .map(|description| format!(" - {}", description))
+                .unwrap_or_default();
+            info!("{}{}{}", variable.name, default, description.dimmed());
+        }
+    }
+
+    for (label, parts) in [
+        ("Variants", config.variants()),
+        ("Features", config.features()),
+    ] {
+        if parts.is_empty() {
+            continue;
+        }
+        println!("\n{} {}", "→".green(), label);
+        for part in parts {
+            let default = if config.default_variant() == Some(part.name()) {
+                " (default)"
+            } else {
+                ""
+            };
+            let description = part
+                .description()
+                .map(|description| format!(" - {}", description))
+                .unwrap_or_default();
+            info!(
+                "{}{}{}",
+                part.name(),
+                default.dimmed(),
+                description.dimmed()
+            );
+        }
+    }
+
+    println!(
+        "\n{} {} action{}",
+        "→".green(),
+        shown.actions.len().to_string().purple(),
+        if shown.actions.len() == 1 { "" } else { "s" }
+    );
+    for (index, action) in shown.actions.iter().enumerate() {
+        let part = action
+            .part
+            .as_ref()
+            .map(|part| format!(" ({})", part))
+            .unwrap_or_default();
+        info!("{}. {}{}", index + 1, action.description, part.dimmed());
+    }
+
+    println!(
+        "\n{} {} file{}",
+        "→".green(),
+        shown.files.len().to_string().purple(),
+        if shown.files.len() == 1 { "" } else { "s" }
+    );
+    for file in &shown.files {
+        let part = file
+            .part
+            .as_ref()
+            .map(|part| format!(", {}", part))
+            .unwrap_or_default();
+        info!(
+            "{}{}",
+            file.name,
+            format!(" ({}{})", human_size(file.size), part).dimmed()
+        );
+    }
+
+    if let Some(readme) = &shown.readme {
+        println!("\n{} README\n", "→".green());
+        println!("{}", markdown::render(readme, "  "));
+    }
+}
+
+/// A file size like `1.2 KiB`
+fn human_size(bytes: usize) -> String {
+    const UNITS: [&str; 3] = ["KiB", "MiB", "GiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
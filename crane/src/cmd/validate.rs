@@ -0,0 +1,188 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process,
+};
+
+use anyhow::anyhow;
+use colored::{Color, Colorize};
+use log::{error, info};
+use serde::Serialize;
+
+use crate::{
+    cmd::{Format, Run, Validate},
+    config::CraneConfig,
+    fuzzy, output,
+};
+use crane_bricks::{
+    brick::{BRICK_CONFIG_FILE, Brick, try_bricks_in_dir},
+    file_utils::sub_dirs,
+    resolve::find_brick,
+    validate::{Problem, Severity, validate_brick},
+};
+
+#[derive(Serialize)]
+struct Validated {
+    name: String,
+    path: PathBuf,
+    problems: Vec<Problem>,
+}
+
+impl Run for Validate {
+    fn run(&self) {
+        let config = CraneConfig::new();
+        let brick_dirs = &config.brick_dirs_or(&self.brick_dirs);
+
+        let paths: Vec<PathBuf> = if self.all {
+            brick_dirs
+                .iter()
+                .flat_map(|dir| sub_dirs(dir).unwrap_or_default())
+                .collect()
+        } else {
+            match self.brick_path(brick_dirs) {
+                Ok(path) => vec![path],
+                Err(error) => {
+                    error!("{}", error);
+                    return;
+                }
+            }
+        };
+        let validated: Vec<Validated> = paths.into_iter().map(validated).collect();
+        if self.format != Format::Text {
+            if let Err(error) = output::print_structured(self.format, &validated) {
+                error!("Could not print problems: {}", error);
+            }
+        } else {
+            print_validated(&validated);
+        }
+        let failed = validated
+            .iter()
+            .flat_map(|brick| &brick.problems)
+            .any(Problem::is_error);
+        if failed {
+            process::exit(1);
+        }
+    }
+}
+
+impl Validate {
+    /// Directory of the brick to check: a path, the name of a brick in the
+    /// brick directories, or the current directory
+    fn brick_path(&self, brick_dirs: &[PathBuf]) -> anyhow::Result<PathBuf> {
+        let Some(name) = &self.brick else {
+            let cwd = env::current_dir()?;
+            if !cwd.join(BRICK_CONFIG_FILE).exists() {
+                return Err(anyhow!(
+                    "There is no {} in the current directory, name a brick or use --all",
+                    BRICK_CONFIG_FILE
+                ));
+            }
+            return Ok(cwd);
+        };
+        if Path::new(name).join(BRICK_CONFIG_FILE).exists() {
+            return Ok(PathBuf::from(name));
+        }
+        let mut bricks: Vec<Brick> = Vec::new();
+        // Bricks that can't be loaded are found by their directory name
+        let mut invalid: Vec<PathBuf> = Vec::new();
+        for brick_dir in brick_dirs {
+            let (loaded, failed) = try_bricks_in_dir(brick_dir);
+            bricks.extend(loaded);
+            invalid.extend(failed.into_iter().map(|(path, _)| path));
+        }
+        if let Some(brick) = find_brick(&bricks, name) {
+            return Ok(brick.path().clone());
+        }
+        if let Some(path) = invalid.into_iter().find(|path| {
+            path.file_name()
+                .is_some_and(|dir| dir.to_string_lossy().eq_ignore_ascii_case(name))
+        }) {
+            return Ok(path);
+        }
+        let suggestions =
+            fuzzy::suggestions(name, bricks.iter().map(|brick| brick.name()));
+        if suggestions.is_empty() {
+            return Err(anyhow!("Could not find brick '{}'", name));
+        }
+        Err(anyhow!(
+            "Could not find brick '{}', did you mean '{}'?",
+            name,
+            suggestions.join("', '")
+        ))
+    }
+}
+
+fn validated(path: PathBuf) -> Validated {
+    let problems = validate_brick(&path);
+    // Bricks that can't be loaded are named after their directory
+    let name = Brick::try_from(path.clone())
+        .map(|brick| brick.name().to_string())
+        .unwrap_or_else(|_| {
+            path.file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default()
+        });
+    Validated {
+        name,
+        path,
+        problems,
+    }
+}
+
+fn print_validated(validated: &[Validated]) {
+    let count = |severity: Severity| {
+        validated
+            .iter()
+            .flat_map(|brick| &brick.problems)
+            .filter(|problem| problem.severity == severity)
+            .count()
+    };
+    for brick in validated {
+        let status = if brick.problems.iter().any(Problem::is_error) {
+            "invalid".red()
+        } else if brick.problems.is_empty() {
+            "ok".green()
+        } else {
+            "ok".yellow()
+        };
+        info!(
+            "{} {}{}",
+            brick.name,
+            status,
+            format!(" ({})", brick.path.display()).dimmed()
+        );
+        let config_file = brick.path.join(BRICK_CONFIG_FILE);
+        let source = fs::read_to_string(&config_file).unwrap_or_default();
+        for problem in &brick.problems {
+            let color = match problem.severity {
+                Severity::Error => Color::Red,
+                Severity::Warning => Color::Yellow,
+            };
+            println!("      {} {}", "⚠".color(color), problem);
+            if problem.span.is_none() {
+                continue;
+            }
+            let diagnostic = problem.diagnostic(&config_file, &source);
+            println!(
+                "        {}",
+                format!("--> {}", diagnostic.location()).dimmed()
+            );
+            for line in output::snippet(&diagnostic, color) {
+                println!("        {}", line);
+            }
+        }
+    }
+
+    let (errors, warnings) = (count(Severity::Error), count(Severity::Warning));
+    let plural = |count: usize| if count == 1 { "" } else { "s" };
+    println!(
+        "{} Checked {} brick{}, found {} error{} and {} warning{}",
+        "→".green(),
+        validated.len().to_string().purple(),
+        plural(validated.len()),
+        errors.to_string().purple(),
+        plural(errors),
+        warnings.to_string().purple(),
+        plural(warnings)
+    );
+}
@@ -1,9 +1,25 @@
-use std::{collections::HashMap, env, fs, path::PathBuf};
+use std::{
+    collections::HashMap,
+    env,
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+    process,
+};
 
+use anyhow::anyhow;
+use colored::Color;
+use crane_bricks::diagnostic::Diagnostic;
+use log::{error, info, warn};
 use serde::{Deserialize, Serialize};
 
+use crate::output;
+
 const ENV_KEY_CONFIG_DIR: &str = "CRANE_CONFIG_DIR";
 
+/// File in a brick dir that defines aliases for the bricks in it
+pub const ALIASES_FILE: &str = "aliases.toml";
+
 fn config_path_from_env() -> anyhow::Result<PathBuf> {
     Ok(PathBuf::from(env::var(ENV_KEY_CONFIG_DIR)?))
 }
@@ -18,24 +34,121 @@ pub fn config_dir() -> PathBuf {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Alias {
     name: String,
-    bricks: Vec<String>,
+    bricks: Vec<AliasEntry>,
+
+    /// The brick dir defining this alias, `None` if it's from the config
+    #[serde(skip)]
+    source: Option<PathBuf>,
 }
 
 impl Alias {
     #[allow(dead_code)]
-    pub fn new(name: String, bricks: Vec<String>) -> Self {
-        Self { name, bricks }
+    pub fn new<E: Into<AliasEntry>>(name: String, bricks: Vec<E>) -> Self {
+        Self {
+            name,
+            bricks: bricks.into_iter().map(Into::into).collect(),
+            source: None,
+        }
+    }
+
+    pub fn with_source(mut self, source: &Path) -> Self {
+        self.source = Some(source.to_path_buf());
+        self
+    }
+
+    pub fn source(&self) -> Option<&Path> {
+        self.source.as_deref()
+    }
+
+    /// Where the alias is defined, for messages
+    pub fn source_label(&self) -> String {
+        match self.source() {
+            Some(dir) => dir.join(ALIASES_FILE).display().to_string(),
+            None => String::from("config"),
+        }
     }
 
     pub fn name(&self) -> &str {
         &self.name
     }
 
-    pub fn bricks(&self) -> &[String] {
+    pub fn bricks(&self) -> &[AliasEntry] {
         &self.bricks
     }
 }
 
+/// A brick of an alias, either a name like `"license:mit"` (which can also be
+/// another alias) or a table with the parameters for the brick
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum AliasEntry {
+    Name(String),
+    Brick(BrickEntry),
+}
+
+impl AliasEntry {
+    /// The entry as brick, splitting names like `brick:variant`
+    pub fn to_brick(&self) -> BrickEntry {
+        match self {
+            AliasEntry::Name(query) => match query.split_once(':') {
+                Some((name, variant)) => BrickEntry {
+                    brick: name.to_string(),
+                    variant: Some(variant.to_string()),
+                    ..Default::default()
+                },
+                None => BrickEntry {
+                    brick: query.clone(),
+                    ..Default::default()
+                },
+            },
+            AliasEntry::Brick(entry) => entry.clone(),
+        }
+    }
+}
+
+impl From<&str> for AliasEntry {
+    fn from(value: &str) -> Self {
+        AliasEntry::Name(value.to_string())
+    }
+}
+
+impl From<String> for AliasEntry {
+    fn from(value: String) -> Self {
+        AliasEntry::Name(value)
+    }
+}
+
+impl Display for AliasEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AliasEntry::Name(name) => write!(f, "{}", name),
+            AliasEntry::Brick(entry) => {
+                write!(f, "{}", entry.brick)?;
+                if let Some(variant) = &entry.variant {
+                    write!(f, ":{}", variant)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// A brick with the variant, features and variables to add it with
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct BrickEntry {
+    pub brick: String,
+
+    #[serde(default)]
+    pub variant: Option<String>,
+
+    #[serde(default)]
+    pub features: Vec<String>,
+
+    /// Variables for this brick, variables set with `--var` take precedence
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct CraneConfig {
     #[serde(default)]
@@ -43,22 +156,44 @@ pub struct CraneConfig {
 
     #[serde(default)]
     alias: Vec<Alias>,
+
+    /// Use the closest brick or alias if a name doesn't exist and only one
+    /// name matches it
+    #[serde(default)]
+    auto_select: bool,
 }
 
 impl CraneConfig {
+    /// Load the config, stops crane with an explanation if the config file
+    /// is invalid
     pub fn new() -> Self {
+        match Self::load() {
+            Ok(config) => config,
+            Err(error) => {
+                error!("Could not load the crane config: {}", error);
+                if let Some(diagnostic) = error.downcast_ref::<Diagnostic>() {
+                    for line in output::snippet(diagnostic, Color::Red) {
+                        eprintln!("      {}", line);
+                    }
+                }
+                info!("Fix the config file, or remove it to use the defaults");
+                process::exit(1);
+            }
+        }
+    }
+
+    /// Load the config, the defaults if there is no config file
+    pub fn load() -> anyhow::Result<Self> {
         let cnf_dir = config_dir();
         let config_file = cnf_dir.join("config.toml");
-        let mut config = {
-            if config_file.exists()
-                && let Ok(parsed_config) = toml::from_str::<CraneConfig>(
-                    fs::read_to_string(config_file).unwrap_or_default().as_str(),
-                )
-            {
-                parsed_config
-            } else {
-                CraneConfig::default()
-            }
+        let mut config = if config_file.exists() {
+            let text = fs::read_to_string(&config_file).map_err(|error| {
+                anyhow!("Could not read '{}': {}", config_file.display(), error)
+            })?;
+            toml::from_str::<CraneConfig>(&text)
+                .map_err(|error| Diagnostic::from_toml(&config_file, &text, &error))?
+        } else {
+            CraneConfig::default()
         };
 
         if config.brick_dirs.is_empty() {
@@ -76,16 +211,88 @@ impl CraneConfig {
                 }
             })
             .collect();
-        config
+        Ok(config)
     }
 
     pub fn brick_dirs(&self) -> &[PathBuf] {
         &self.brick_dirs
     }
 
+    /// The brick dirs given on the command line, the configured ones if none
+    /// are given
+    pub fn brick_dirs_or(&self, brick_dirs: &Option<Vec<PathBuf>>) -> Vec<PathBuf> {
+        match brick_dirs {
+            Some(brick_dirs) if !brick_dirs.is_empty() => brick_dirs.clone(),
+            _ => self.brick_dirs.clone(),
+        }
+    }
+
     pub fn alias(&self) -> &[Alias] {
         &self.alias
     }
+
+    pub fn auto_select(&self) -> bool {
+        self.auto_select
+    }
+
+    /// Aliases of the config merged with the aliases of the brick dirs.
+    /// The config takes precedence, then the brick dirs in order.
+    pub fn aliases(&self, brick_dirs: &[PathBuf]) -> Vec<Alias> {
+        let mut sources = vec![self.alias().to_vec()];
+        sources.extend(brick_dirs.iter().map(|dir| aliases_in_dir(dir)));
+        merge_aliases(sources)
+    }
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct AliasesFile {
+    #[serde(default)]
+    alias: Vec<Alias>,
+}
+
+/// Load the aliases file of a brick dir, if there is one
+pub fn aliases_in_dir(dir: &Path) -> Vec<Alias> {
+    let path = dir.join(ALIASES_FILE);
+    if !path.exists() {
+        return Vec::new();
+    }
+    let parsed = fs::read_to_string(&path)
+        .map_err(|error| anyhow!("Could not read '{}': {}", path.display(), error))
+        .and_then(|content| {
+            toml::from_str::<AliasesFile>(&content)
+                .map_err(|error| Diagnostic::from_toml(&path, &content, &error).into())
+        });
+    match parsed {
+        Ok(file) => file
+            .alias
+            .into_iter()
+            .map(|alias| alias.with_source(dir))
+            .collect(),
+        Err(error) => {
+            warn!("Could not load aliases: {}", error);
+            Vec::new()
+        }
+    }
+}
+
+/// Merge lists of aliases, the first definition of a name wins
+pub fn merge_aliases(sources: Vec<Vec<Alias>>) -> Vec<Alias> {
+    let mut merged: Vec<Alias> = Vec::new();
+    for alias in sources.into_iter().flatten() {
+        match merged.iter().find(|existing| {
+            existing.name().to_lowercase() == alias.name().to_lowercase()
+        }) {
+            Some(existing) if existing.bricks() != alias.bricks() => warn!(
+                "Alias '{}' from {} is overridden by {}",
+                alias.name(),
+                alias.source_label(),
+                existing.source_label()
+            ),
+            Some(_) => {}
+            None => merged.push(alias),
+        }
+    }
+    merged
 }
 
 /// Converts a list of aliases to a map where the brick
@@ -93,15 +300,11 @@ impl CraneConfig {
 pub fn map_aliases(aliases: &[Alias]) -> HashMap<String, Vec<String>> {
     let mut brick_map: HashMap<String, Vec<String>> = HashMap::new();
     for alias in aliases {
-        for brick in alias.bricks() {
-            if brick_map.contains_key(brick) {
-                brick_map
-                    .get_mut(brick)
-                    .unwrap()
-                    .push(alias.name().to_string());
-            } else {
-                brick_map.insert(brick.to_string(), vec![alias.name().to_string()]);
-            }
+        for entry in alias.bricks() {
+            brick_map
+                .entry(entry.to_brick().brick)
+                .or_default()
+                .push(alias.name().to_string());
         }
     }
     brick_map
@@ -147,4 +350,58 @@ mod tests {
         brick_map.insert(String::from("brick_c"), vec![String::from("world")]);
         assert_eq!(map_aliases(&aliases), brick_map);
     }
+
+    #[test]
+    fn test_alias_entries() {
+        let config: CraneConfig = toml::from_str(
+            r#"
+            [[alias]]
+            name = "rust-oss"
+            bricks = [
+                "rustfmt",
+                { brick = "license", variant = "mit", variables = { author = "ACME" } },
+            ]
+            "#,
+        )
+        .unwrap();
+        let bricks = config.alias()[0].bricks();
+        assert_eq!(bricks[0], AliasEntry::from("rustfmt"));
+        let license = bricks[1].to_brick();
+        assert_eq!(license.brick, "license");
+        assert_eq!(license.variant.as_deref(), Some("mit"));
+        assert_eq!(license.variables.get("author").unwrap(), "ACME");
+        assert_eq!(
+            AliasEntry::from("license:apache")
+                .to_brick()
+                .variant
+                .as_deref(),
+            Some("apache")
+        );
+    }
+
+    #[test]
+    fn test_brick_dirs_or() {
+        let config: CraneConfig = toml::from_str(r#"brick_dirs = ["bricks"]"#).unwrap();
+        let given = vec![PathBuf::from("other")];
+        assert_eq!(config.brick_dirs_or(&Some(given.clone())), given);
+        assert_eq!(config.brick_dirs_or(&Some(Vec::new())), config.brick_dirs());
+        assert_eq!(config.brick_dirs_or(&None), config.brick_dirs());
+    }
+
+    #[test]
+    fn test_merge_aliases() {
+        let config = vec![Alias::new(String::from("rust"), vec![String::from("mit")])];
+        let team = vec![
+            Alias::new(String::from("Rust"), vec![String::from("apache")])
+                .with_source(Path::new("team")),
+            Alias::new(String::from("ci"), vec![String::from("github-ci")])
+                .with_source(Path::new("team")),
+        ];
+        let merged = merge_aliases(vec![config, team]);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].bricks(), [AliasEntry::from("mit")]);
+        assert_eq!(merged[0].source(), None);
+        assert_eq!(merged[1].name(), "ci");
+        assert_eq!(merged[1].source(), Some(Path::new("team")));
+    }
 }
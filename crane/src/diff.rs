@@ -0,0 +1,66 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use colored::Colorize;
+use crane_bricks::file_utils::is_binary;
+use similar::{ChangeTag, TextDiff};
+
+/// Lines of unchanged context around every change
+const CONTEXT_LINES: usize = 3;
+
+/// Print the changes a dry run would make to the files in `cwd`
+pub fn print_planned(cwd: &Path, planned: &[(PathBuf, Option<Vec<u8>>)]) {
+    for (path, content) in planned {
+        let original = fs::read(path).ok();
+        if &original == content {
+            continue;
+        }
+        let name = path.strip_prefix(cwd).unwrap_or(path).display();
+        let verb = match (&original, content) {
+            (None, _) => "Create",
+            (Some(_), None) => "Remove",
+            (Some(_), Some(_)) => "Modify",
+        };
+        println!("\n{} {} '{}'", "→".green(), verb, name);
+        let original = original.unwrap_or_default();
+        let content = content.clone().unwrap_or_default();
+        if is_binary(&original) || is_binary(&content) {
+            println!("  {}", "(binary file)".dimmed());
+            continue;
+        }
+        let original = String::from_utf8_lossy(&original);
+        let content = String::from_utf8_lossy(&content);
+        print_diff(&original, &content);
+    }
+}
+
+fn print_diff(original: &str, content: &str) {
+    let diff = TextDiff::from_lines(original, content);
+    for (index, group) in diff.grouped_ops(CONTEXT_LINES).iter().enumerate() {
+        if index > 0 {
+            println!("  {}", "...".dimmed());
+        }
+        for op in group {
+            for change in diff.iter_changes(op) {
+                let line = change.value().trim_end_matches('\n');
+                match change.tag() {
+                    ChangeTag::Delete => println!("  {}", format!("-{}", line).red()),
+                    ChangeTag::Insert => println!("  {}", format!("+{}", line).green()),
+                    ChangeTag::Equal => println!("  {}", format!(" {}", line).dimmed()),
+                }
+            }
+        }
+    }
+}
+
+/// The hunks of a unified diff between two texts, without file headers
+pub fn unified_hunks(original: &str, content: &str) -> String {
+    TextDiff::from_lines(original, content)
+        .unified_diff()
+        .context_radius(CONTEXT_LINES)
+        .iter_hunks()
+        .map(|hunk| hunk.to_string())
+        .collect()
+}
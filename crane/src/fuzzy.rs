@@ -0,0 +1,53 @@
+use std::cmp::Reverse;
+
+use fuzzy_matcher::{FuzzyMatcher, skim::SkimMatcherV2};
+
+/// How many names are suggested for an unknown brick
+const SUGGESTION_COUNT: usize = 3;
+
+/// How well the text matches the query, `None` if it doesn't match
+pub fn score(query: &str, text: &str) -> Option<i64> {
+    SkimMatcherV2::default()
+        .ignore_case()
+        .fuzzy_match(text, query)
+}
+
+/// All names matching the query, best match first
+pub fn rank<'a>(query: &str, names: impl IntoIterator<Item = &'a str>) -> Vec<&'a str> {
+    let matcher = SkimMatcherV2::default().ignore_case();
+    let mut matches: Vec<(&str, i64)> = Vec::new();
+    for name in names {
+        if matches.iter().any(|(existing, _)| *existing == name) {
+            continue;
+        }
+        if let Some(score) = matcher.fuzzy_match(name, query) {
+            matches.push((name, score));
+        }
+    }
+    // Stable sort keeps the original order for equal scores
+    matches.sort_by_key(|(_, score)| Reverse(*score));
+    matches.into_iter().map(|(name, _)| name).collect()
+}
+
+/// The best matches for a name that doesn't exist
+pub fn suggestions<'a>(
+    query: &str,
+    names: impl IntoIterator<Item = &'a str>,
+) -> Vec<&'a str> {
+    let mut ranked = rank(query, names);
+    ranked.truncate(SUGGESTION_COUNT);
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rank() {
+        let names = ["rustfmt", "serde", "license", "serde-json"];
+        assert_eq!(rank("serd", names), vec!["serde", "serde-json"]);
+        assert_eq!(rank("LIC", names), vec!["license"]);
+        assert!(rank("xyz", names).is_empty());
+    }
+}
@@ -2,9 +2,17 @@ use clap::Parser;
 
 use crate::cmd::{CraneCli, Run};
 
+mod alias;
 mod cmd;
 mod config;
+mod diff;
+mod fuzzy;
 mod logging;
+mod markdown;
+mod output;
+mod picker;
+mod prompt;
+mod snapshot;
 
 fn main() {
     let cli = CraneCli::parse();
@@ -0,0 +1,136 @@
+use colored::Colorize;
+use pulldown_cmark::{CodeBlockKind, Event, Parser, Tag, TagEnd};
+
+/// Render markdown for the terminal, every line starts with `indent`
+pub fn render(markdown: &str, indent: &str) -> String {
+    let mut output = String::new();
+    let mut line = String::new();
+    let mut bold = false;
+    let mut italic = false;
+    let mut heading = false;
+    let mut code_block = false;
+    let mut lists: Vec<Option<u64>> = Vec::new();
+    let mut links: Vec<String> = Vec::new();
+
+    let flush = |line: &mut String, output: &mut String| {
+        if !line.is_empty() {
+            output.push_str(indent);
+            output.push_str(line.trim_end());
+            output.push('\n');
+            line.clear();
+        }
+    };
+
+    for event in Parser::new(markdown) {
+        match event {
+            Event::Start(Tag::Heading { .. }) => heading = true,
+            Event::End(TagEnd::Heading(_)) => {
+                heading = false;
+                flush(&mut line, &mut output);
+                output.push('\n');
+            }
+            Event::End(TagEnd::Paragraph) => {
+                flush(&mut line, &mut output);
+                if lists.is_empty() {
+                    output.push('\n');
+                }
+            }
+            Event::Start(Tag::CodeBlock(kind)) => {
+                code_block = true;
+                if let CodeBlockKind::Fenced(language) = kind
+                    && !language.is_empty()
+                {
+                    line.push_str(&format!("[{}]", language).dimmed().to_string());
+                    flush(&mut line, &mut output);
+                }
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                code_block = false;
+                output.push('\n');
+            }
+            Event::Start(Tag::List(start)) => {
+                flush(&mut line, &mut output);
+                lists.push(start);
+            }
+            Event::End(TagEnd::List(_)) => {
+                lists.pop();
+                if lists.is_empty() {
+                    output.push('\n');
+                }
+            }
+            Event::Start(Tag::Item) => {
+                flush(&mut line, &mut output);
+                let depth = lists.len().saturating_sub(1);
+                let bullet = match lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}.", *number - 1)
+                    }
+                    _ => String::from("•"),
+                };
+                line.push_str(&format!("{}{} ", "  ".repeat(depth), bullet.dimmed()));
+            }
+            Event::End(TagEnd::Item) => flush(&mut line, &mut output),
+            Event::Start(Tag::Strong) => bold = true,
+            Event::End(TagEnd::Strong) => bold = false,
+            Event::Start(Tag::Emphasis) => italic = true,
+            Event::End(TagEnd::Emphasis) => italic = false,
+            Event::Start(Tag::Link { dest_url, .. }) => links.push(dest_url.to_string()),
+            Event::End(TagEnd::Link) => {
+                if let Some(url) = links.pop() {
+                    line.push_str(&format!(" ({})", url).dimmed().to_string());
+                }
+            }
+            Event::Text(text) if code_block => {
+                for code_line in text.lines() {
+                    output.push_str(indent);
+                    output.push_str(&format!("    {}", code_line).dimmed().to_string());
+                    output.push('\n');
+                }
+            }
+            Event::Text(text) => {
+                let mut styled = text.normal();
+                if heading {
+                    styled = styled.bold().purple();
+                }
+                if bold {
+                    styled = styled.bold();
+                }
+                if italic {
+                    styled = styled.italic();
+                }
+                line.push_str(&styled.to_string());
+            }
+            Event::Code(code) => line.push_str(&code.yellow().to_string()),
+            Event::SoftBreak => line.push(' '),
+            Event::HardBreak => flush(&mut line, &mut output),
+            Event::Rule => {
+                flush(&mut line, &mut output);
+                output.push_str(indent);
+                output.push_str(&"─".repeat(20).dimmed().to_string());
+                output.push_str("\n\n");
+            }
+            _ => {}
+        }
+    }
+    flush(&mut line, &mut output);
+    output.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_markdown() {
+        colored::control::set_override(false);
+        let rendered = render(
+            "# License\n\nUse **this** with `crane add`.\n\n* one\n* [two](https://example.com)\n",
+            "  ",
+        );
+        assert_eq!(
+            rendered,
+            "  License\n\n  Use this with crane add.\n\n  • one\n  • two (https://example.com)"
+        );
+    }
+}
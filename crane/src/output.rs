@@ -0,0 +1,40 @@
+use colored::{Color, Colorize};
+use crane_bricks::{diagnostic::Diagnostic, event::Event};
+use serde::Serialize;
+
+use crate::cmd::Format;
+
+/// Print a value as JSON or YAML
+pub fn print_structured(format: Format, value: &impl Serialize) -> anyhow::Result<()> {
+    match format {
+        Format::Text => {}
+        Format::Json => println!("{}", serde_json::to_string_pretty(value)?),
+        Format::Yaml => print!("{}", serde_yaml::to_string(value)?),
+    }
+    Ok(())
+}
+
+/// Print an event as a line of JSON, or as a YAML document
+pub fn print_event(format: Format, event: &Event) {
+    let printed = match format {
+        Format::Text => return,
+        Format::Json => serde_json::to_string(event)
+            .map(|json| format!("{}\n", json))
+            .map_err(anyhow::Error::from),
+        Format::Yaml => serde_yaml::to_string(event)
+            .map(|yaml| format!("---\n{}", yaml))
+            .map_err(anyhow::Error::from),
+    };
+    match printed {
+        Ok(printed) => print!("{}", printed),
+        Err(error) => log::error!("Could not print event {:?}: {}", event, error),
+    }
+}
+
+/// The line of the file a diagnostic points at, and the marker below it
+pub fn snippet(diagnostic: &Diagnostic, color: Color) -> Vec<String> {
+    match diagnostic.snippet() {
+        Some((line, marker)) => vec![line, marker.color(color).to_string()],
+        None => Vec::new(),
+    }
+}
@@ -0,0 +1,339 @@
+use crane_bricks::brick::Brick;
+use ratatui::{
+    DefaultTerminal, Frame,
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style, Stylize},
+    text::Line,
+    widgets::{Block, List, ListItem, ListState, Paragraph, Wrap},
+};
+
+use crate::{config::Alias, fuzzy};
+
+/// A brick or alias that can be picked
+#[derive(Debug, Clone)]
+pub struct PickerItem {
+    name: String,
+    description: Option<String>,
+
+    /// Lines shown in the preview pane
+    preview: Vec<String>,
+}
+
+impl PickerItem {
+    pub fn from_brick(brick: &Brick) -> Self {
+        let config = brick.config();
+        let mut preview = Vec::new();
+        if let Some(version) = config.version() {
+            preview.push(format!("Version: {}", version));
+        }
+        if !config.authors().is_empty() {
+            preview.push(format!("Authors: {}", config.authors().join(", ")));
+        }
+        if !config.tags().is_empty() {
+            preview.push(format!("Tags: {}", config.tags().join(", ")));
+        }
+        if let Some(homepage) = config.homepage() {
+            preview.push(format!("Homepage: {}", homepage));
+        }
+        if let Some(base) = config.extends() {
+            preview.push(format!("Extends '{}'", base));
+        }
+        for (label, parts) in [
+            ("Variants", config.variants()),
+            ("Features", config.features()),
+        ] {
+            if !parts.is_empty() {
+                let names: Vec<&str> = parts.iter().map(|part| part.name()).collect();
+                preview.push(format!("{}: {}", label, names.join(", ")));
+            }
+        }
+        preview.push(String::from("Files:"));
+        for file in brick.files() {
+            preview.push(format!("  {}", file.name()));
+        }
+        preview.push(String::from("Actions:"));
+        for action in brick.actions() {
+            let sources = &action.common().sources;
+            if sources.is_empty() {
+                preview.push(format!("  {}", action.name()));
+            } else {
+                preview.push(format!("  {} ({})", action.name(), sources.join(", ")));
+            }
+        }
+        Self {
+            name: brick.name().to_string(),
+            description: config.description().map(str::to_string),
+            preview,
+        }
+    }
+
+    pub fn from_alias(alias: &Alias) -> Self {
+        let mut preview = vec![String::from("Bricks:")];
+        for entry in alias.bricks() {
+            preview.push(format!("  {}", entry));
+        }
+        Self {
+            name: alias.name().to_string(),
+            description: Some(format!("Alias from {}", alias.source_label())),
+            preview,
+        }
+    }
+}
+
+/// What the user did with a key press
+#[derive(Debug, PartialEq, Eq)]
+enum Outcome {
+    Confirmed(Vec<String>),
+    Cancelled,
+}
+
+/// State of the brick picker
+struct Picker {
+    items: Vec<PickerItem>,
+    query: String,
+
+    /// Indexes of the items matching the query, best match first
+    matches: Vec<usize>,
+
+    /// Indexes of the selected items, in the order they were selected
+    selected: Vec<usize>,
+
+    list: ListState,
+}
+
+impl Picker {
+    fn new(items: Vec<PickerItem>) -> Self {
+        let mut picker = Self {
+            items,
+            query: String::new(),
+            matches: Vec::new(),
+            selected: Vec::new(),
+            list: ListState::default(),
+        };
+        picker.update_matches();
+        picker
+    }
+
+    fn update_matches(&mut self) {
+        self.matches = if self.query.is_empty() {
+            (0..self.items.len()).collect()
+        } else {
+            let names = self.items.iter().map(|item| item.name.as_str());
+            fuzzy::rank(&self.query, names)
+                .into_iter()
+                .filter_map(|name| self.items.iter().position(|item| item.name == name))
+                .collect()
+        };
+        self.list.select(if self.matches.is_empty() {
+            None
+        } else {
+            Some(0)
+        });
+    }
+
+    /// Index of the highlighted item
+    fn current(&self) -> Option<usize> {
+        self.list
+            .selected()
+            .and_then(|index| self.matches.get(index).copied())
+    }
+
+    fn toggle(&mut self) {
+        let Some(current) = self.current() else {
+            return;
+        };
+        match self.selected.iter().position(|index| *index == current) {
+            Some(position) => {
+                self.selected.remove(position);
+            }
+            None => self.selected.push(current),
+        }
+    }
+
+    /// Names of the selected items, or the highlighted one if none is selected
+    fn picked(&self) -> Vec<String> {
+        if self.selected.is_empty() {
+            return self
+                .current()
+                .map(|index| vec![self.items[index].name.clone()])
+                .unwrap_or_default();
+        }
+        self.selected
+            .iter()
+            .map(|index| self.items[*index].name.clone())
+            .collect()
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Option<Outcome> {
+        match key.code {
+            KeyCode::Esc => return Some(Outcome::Cancelled),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Some(Outcome::Cancelled);
+            }
+            KeyCode::Enter => return Some(Outcome::Confirmed(self.picked())),
+            KeyCode::Tab | KeyCode::Char(' ') => {
+                self.toggle();
+                self.list.select_next();
+            }
+            KeyCode::Down => self.list.select_next(),
+            KeyCode::Up => self.list.select_previous(),
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.update_matches();
+            }
+            KeyCode::Char(char) => {
+                self.query.push(char);
+                self.update_matches();
+            }
+            _ => {}
+        }
+        // The list state doesn't know how many items there are
+        if let Some(index) = self.list.selected()
+            && index >= self.matches.len()
+        {
+            self.list.select(self.matches.len().checked_sub(1));
+        }
+        None
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [search_area, main_area, help_area] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(1),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [list_area, preview_area] =
+            Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)])
+                .areas(main_area);
+
+        frame.render_widget(
+            Paragraph::new(format!("> {}", self.query))
+                .block(Block::bordered().title(" Search ")),
+            search_area,
+        );
+
+        let items: Vec<ListItem> = self
+            .matches
+            .iter()
+            .map(|index| {
+                let item = &self.items[*index];
+                let marker = if self.selected.contains(index) {
+                    "[x]"
+                } else {
+                    "[ ]"
+                };
+                let mut line = Line::from(format!("{} {}", marker, item.name));
+                if let Some(description) = &item.description {
+                    line.push_span(format!(" - {}", description).dark_gray());
+                }
+                ListItem::new(line)
+            })
+            .collect();
+        let title = format!(" Bricks ({} selected) ", self.selected.len());
+        frame.render_stateful_widget(
+            List::new(items)
+                .block(Block::bordered().title(title))
+                .highlight_style(
+                    Style::new().fg(Color::Magenta).add_modifier(Modifier::BOLD),
+                )
+                .highlight_symbol("→ "),
+            list_area,
+            &mut self.list,
+        );
+
+        let preview: Vec<Line> = match self.current() {
+            Some(index) => {
+                let item = &self.items[index];
+                let mut lines = vec![Line::from(item.name.clone().bold())];
+                if let Some(description) = &item.description {
+                    lines.push(Line::from(description.clone()));
+                }
+                lines.push(Line::default());
+                lines.extend(item.preview.iter().map(|line| Line::from(line.clone())));
+                lines
+            }
+            None => vec![Line::from("No matches".dark_gray())],
+        };
+        frame.render_widget(
+            Paragraph::new(preview)
+                .block(Block::bordered().title(" Preview "))
+                .wrap(Wrap { trim: false }),
+            preview_area,
+        );
+
+        frame.render_widget(
+            Line::from(
+                "↑↓ move • space/tab select • enter confirm • esc cancel".dark_gray(),
+            ),
+            help_area,
+        );
+    }
+}
+
+/// Let the user pick bricks and aliases, `None` if cancelled
+pub fn pick(items: Vec<PickerItem>) -> anyhow::Result<Option<Vec<String>>> {
+    let mut terminal = ratatui::init();
+    let result = run(&mut terminal, Picker::new(items));
+    ratatui::restore();
+    result
+}
+
+fn run(
+    terminal: &mut DefaultTerminal,
+    mut picker: Picker,
+) -> anyhow::Result<Option<Vec<String>>> {
+    loop {
+        terminal.draw(|frame| picker.draw(frame))?;
+        if let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            match picker.handle_key(key) {
+                Some(Outcome::Confirmed(names)) => return Ok(Some(names)),
+                Some(Outcome::Cancelled) => return Ok(None),
+                None => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(name: &str) -> PickerItem {
+        PickerItem {
+            name: name.to_string(),
+            description: None,
+            preview: Vec::new(),
+        }
+    }
+
+    fn press(picker: &mut Picker, code: KeyCode) -> Option<Outcome> {
+        picker.handle_key(KeyEvent::from(code))
+    }
+
+    #[test]
+    fn test_picker_search_and_select() {
+        let mut picker =
+            Picker::new(vec![item("license"), item("rustfmt"), item("serde")]);
+        // Without a selection, the highlighted item is picked
+        assert_eq!(picker.picked(), vec!["license"]);
+
+        press(&mut picker, KeyCode::Char(' '));
+        for char in "serd".chars() {
+            press(&mut picker, KeyCode::Char(char));
+        }
+        assert_eq!(picker.matches, vec![2]);
+        press(&mut picker, KeyCode::Tab);
+        assert_eq!(
+            press(&mut picker, KeyCode::Enter),
+            Some(Outcome::Confirmed(vec![
+                String::from("license"),
+                String::from("serde")
+            ]))
+        );
+        assert_eq!(press(&mut picker, KeyCode::Esc), Some(Outcome::Cancelled));
+    }
+}
@@ -0,0 +1,78 @@
+use std::io::{self, IsTerminal};
+
+use crane_bricks::brick::{Brick, BrickPart, Variable};
+use dialoguer::{Confirm, Input, MultiSelect, Select, theme::ColorfulTheme};
+
+/// If crane can ask the user questions
+pub fn is_interactive() -> bool {
+    io::stdin().is_terminal() && io::stdout().is_terminal()
+}
+
+fn part_label(part: &BrickPart) -> String {
+    match part.description() {
+        Some(description) => format!("{} - {}", part.name(), description),
+        None => part.name().to_string(),
+    }
+}
+
+/// Ask which variant of the brick should be used
+pub fn select_variant(brick: &Brick) -> anyhow::Result<Option<String>> {
+    let variants = brick.config().variants();
+    if variants.is_empty() {
+        return Ok(None);
+    }
+    let default = brick
+        .config()
+        .default_variant()
+        .and_then(|name| variants.iter().position(|variant| variant.name() == name))
+        .unwrap_or(0);
+    let index = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!("Which variant of '{}'?", brick.name()))
+        .items(variants.iter().map(part_label))
+        .default(default)
+        .interact()?;
+    Ok(Some(variants[index].name().to_string()))
+}
+
+/// Ask which features of the brick should be added
+pub fn select_features(brick: &Brick) -> anyhow::Result<Vec<String>> {
+    let features = brick.config().features();
+    if features.is_empty() {
+        return Ok(Vec::new());
+    }
+    let indexes = MultiSelect::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!("Which features of '{}'?", brick.name()))
+        .items(features.iter().map(part_label))
+        .interact()?;
+    Ok(indexes
+        .into_iter()
+        .map(|index| features[index].name().to_string())
+        .collect())
+}
+
+/// Ask for the value of a variable of the brick
+pub fn input_variable(brick: &Brick, variable: &Variable) -> anyhow::Result<String> {
+    let prompt = match variable.description() {
+        Some(description) => format!(
+            "{} ({}) for '{}'",
+            variable.name(),
+            description,
+            brick.name()
+        ),
+        None => format!("{} for '{}'", variable.name(), brick.name()),
+    };
+    let theme = ColorfulTheme::default();
+    let mut input = Input::<String>::with_theme(&theme).with_prompt(prompt);
+    if let Some(default) = variable.default() {
+        input = input.default(default.to_string());
+    }
+    Ok(input.interact_text()?)
+}
+
+/// Ask the user to confirm something
+pub fn confirm(prompt: &str) -> anyhow::Result<bool> {
+    Ok(Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt(prompt)
+        .default(true)
+        .interact()?)
+}
@@ -0,0 +1,67 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::anyhow;
+use crane_bricks::{file_utils::SKIPPED_DIRS, project::PROJECT_DIR};
+
+const SNAPSHOT_DIR: &str = "snapshot";
+
+/// Where the snapshot of a project is stored
+pub fn snapshot_dir(project_dir: &Path) -> PathBuf {
+    project_dir.join(PROJECT_DIR).join(SNAPSHOT_DIR)
+}
+
+/// All files in a directory by their path relative to it, separated by `/`
+pub fn project_files(dir: &Path) -> anyhow::Result<BTreeMap<String, PathBuf>> {
+    let mut files = BTreeMap::new();
+    add_files(dir, "", &mut files)?;
+    Ok(files)
+}
+
+fn add_files(
+    dir: &Path,
+    prefix: &str,
+    files: &mut BTreeMap<String, PathBuf>,
+) -> anyhow::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = format!("{}{}", prefix, entry.file_name().display());
+        // Symlinks are not followed
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            if !SKIPPED_DIRS
+                .iter()
+                .any(|skipped| entry.file_name() == *skipped)
+            {
+                add_files(&entry.path(), &format!("{}/", name), files)?;
+            }
+        } else if file_type.is_file() {
+            files.insert(name, entry.path());
+        }
+    }
+    Ok(())
+}
+
+/// Copy all files of the project into its snapshot, replacing an older one.
+/// Returns how many files were copied.
+pub fn take(project_dir: &Path) -> anyhow::Result<usize> {
+    let snapshot = snapshot_dir(project_dir);
+    if snapshot.exists() {
+        fs::remove_dir_all(&snapshot)?;
+    }
+    let files = project_files(project_dir)?;
+    for (name, path) in &files {
+        let target = snapshot.join(name);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(path, &target)
+            .map_err(|error| anyhow!("Could not copy '{}': {}", name, error))?;
+    }
+    // An empty project still has a snapshot
+    fs::create_dir_all(&snapshot)?;
+    Ok(files.len())
+}
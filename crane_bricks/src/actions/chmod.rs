@@ -0,0 +1,61 @@
+use std::path::Path;
+
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    actions::{ExecuteAction, common::Common},
+    brick::Brick,
+    context::ActionContext,
+    file_utils::{file_set_mode, parse_mode},
+};
+
+/// Change the permissions of existing files in the project.
+///
+/// ## Example
+///
+/// ### Config
+///
+/// ```toml
+/// [[actions]]
+/// action = "chmod"
+/// sources = [
+///     "scripts/release.sh"
+/// ]
+/// mode = "0755"
+/// ```
+///
+/// ### Result
+///
+/// Makes `scripts/release.sh` executable.
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq, Eq)]
+pub struct ChmodAction {
+    #[serde(flatten)]
+    pub common: Common,
+
+    /// Octal permissions, e.g. `"0755"`
+    pub mode: String,
+}
+
+impl ExecuteAction for ChmodAction {
+    fn execute(
+        &self,
+        context: &ActionContext,
+        _brick: &Brick,
+        cwd: &Path,
+    ) -> anyhow::Result<()> {
+        let mode = parse_mode(&self.mode)?;
+        if self.common.sources.is_empty() {
+            warn!("No files to change permissions of!");
+        }
+        for source in &self.common.sources {
+            let target_path = cwd.join(source);
+            if !target_path.exists() {
+                return Err(anyhow!("Target file '{}' does not exist!", source));
+            }
+            info!("Changing mode of '{}' to {:o}", source, mode);
+            file_set_mode(context, &target_path, mode)?;
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,12 @@
-use serde::Deserialize;
+use std::path::{Path, PathBuf};
 
-#[derive(Debug, Deserialize, Default, Clone, PartialEq, Eq)]
+use anyhow::anyhow;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{condition::Condition, file_utils::glob_paths};
+
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq, Eq)]
 pub struct Common {
     /// Relative path from where you run crane to where the files should go
     ///
@@ -8,7 +14,7 @@ pub struct Common {
     /// [[actions]]
     /// working_dir = "./src/"
     /// ```
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub working_dir: Option<String>,
 
     /// List of paths including for which files the action should run
@@ -21,6 +27,61 @@ pub struct Common {
     /// # Or regex
     /// sources = [ "re:.+\.md", "LICENSE"]
     /// ```
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub sources: Vec<String>,
+
+    /// Only run the action if the condition is met
+    ///
+    /// ```toml
+    /// [[actions]]
+    /// when = { file_exists = "Cargo.toml" }
+    /// ```
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub when: Option<Condition>,
+}
+
+impl Common {
+    /// If `name` is one of the sources, either by name or by a `re:` regex
+    pub fn matches_source(&self, name: &str) -> bool {
+        self.sources
+            .iter()
+            .any(|source| source_matches(source, name))
+    }
+}
+
+/// If `name` is the source, or matches it if it's a `re:` regex
+pub fn source_matches(source: &str, name: &str) -> bool {
+    match source.strip_prefix("re:") {
+        Some(pattern) => Regex::new(&format!("^(?:{})$", pattern))
+            .is_ok_and(|regex| regex.is_match(name)),
+        None => source == name,
+    }
+}
+
+/// Resolve the `from` glob and `to` path of a move or copy into pairs of
+/// source and destination paths.
+///
+/// If the glob matches multiple files, `to` ends with a `/` or `to` is an
+/// existing directory, the files keep their names and are placed inside `to`.
+pub(crate) fn resolve_transfers(
+    cwd: &Path,
+    from: &str,
+    to: &str,
+) -> anyhow::Result<Vec<(PathBuf, PathBuf)>> {
+    let sources = glob_paths(cwd, from)?;
+    let to_path = cwd.join(to);
+    let into_dir = sources.len() > 1 || to.ends_with('/') || to_path.is_dir();
+    sources
+        .into_iter()
+        .map(|source| {
+            if !into_dir {
+                return Ok((source, to_path.clone()));
+            }
+            let name = source
+                .file_name()
+                .ok_or_else(|| anyhow!("Invalid source path '{}'", source.display()))?;
+            let destination = to_path.join(name);
+            Ok((source, destination))
+        })
+        .collect()
 }
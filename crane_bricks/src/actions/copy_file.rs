@@ -0,0 +1,94 @@
+use std::path::Path;
+
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    actions::{
+        ExecuteAction,
+        common::{Common, resolve_transfers},
+        insert_file::FileExistsAction,
+    },
+    brick::{Brick, is_default},
+    context::ActionContext,
+    file_utils::{file_append_content, file_copy, is_binary, path_exists},
+};
+
+/// Copy files within the project.
+///
+/// ## Example
+///
+/// ### Config
+///
+/// ```toml
+/// [[actions]]
+/// action = "copy_file"
+/// from = ".github/workflows/ci.yml"
+/// to = "packages/core/.github/workflows/"
+/// ```
+///
+/// ### Result
+///
+/// Copies the workflow into the `core` package.
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq, Eq)]
+pub struct CopyFileAction {
+    #[serde(flatten)]
+    pub common: Common,
+
+    /// Path or glob of the files to copy
+    pub from: String,
+
+    /// Path of the copy, or a directory if `from` matches multiple files
+    pub to: String,
+
+    /// Define what happens if the destination already exists
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub if_file_exists: FileExistsAction,
+}
+
+impl ExecuteAction for CopyFileAction {
+    fn execute(
+        &self,
+        context: &ActionContext,
+        _brick: &Brick,
+        cwd: &Path,
+    ) -> anyhow::Result<()> {
+        let transfers = resolve_transfers(cwd, &self.from, &self.to)?;
+        if transfers.is_empty() {
+            warn!("No files found matching '{}'", self.from);
+        }
+        for (source, destination) in transfers {
+            info!(
+                "Copying '{}' to '{}'",
+                source.display(),
+                destination.display()
+            );
+            if !path_exists(&destination) {
+                file_copy(context, &source, &destination)?;
+                continue;
+            }
+            warn!("File '{}' already exists", destination.display());
+            match &self.if_file_exists {
+                FileExistsAction::Append => {
+                    let content = std::fs::read(&source)?;
+                    if is_binary(&content) {
+                        return Err(anyhow!(
+                            "Cannot append '{}', binary files can only be replaced",
+                            source.display()
+                        ));
+                    }
+                    info!("Appending content to file");
+                    file_append_content(context, &destination, content)?;
+                }
+                FileExistsAction::Replace => {
+                    info!("Replacing file");
+                    file_copy(context, &source, &destination)?;
+                }
+                FileExistsAction::Pass => {
+                    info!("Continuing");
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,50 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    actions::{ExecuteAction, common::Common},
+    brick::Brick,
+    context::ActionContext,
+    file_utils::dir_create_all,
+};
+
+/// Create a directory, including all missing parents.
+///
+/// ## Example
+///
+/// ### Config
+///
+/// ```toml
+/// [[actions]]
+/// action = "create_dir"
+/// path = "src/bin"
+/// ```
+///
+/// ### Result
+///
+/// Creates `src/bin`, does nothing if it already exists.
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq, Eq)]
+pub struct CreateDirAction {
+    #[serde(flatten)]
+    pub common: Common,
+
+    pub path: String,
+}
+
+impl ExecuteAction for CreateDirAction {
+    fn execute(
+        &self,
+        context: &ActionContext,
+        _brick: &Brick,
+        cwd: &Path,
+    ) -> anyhow::Result<()> {
+        let path = cwd.join(&self.path);
+        if path.is_dir() {
+            info!("Directory '{}' already exists", self.path);
+            return Ok(());
+        }
+        info!("Creating directory '{}'", self.path);
+        dir_create_all(context, &path)
+    }
+}
@@ -0,0 +1,55 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    actions::{ExecuteAction, common::Common},
+    brick::Brick,
+    context::ActionContext,
+    file_utils::{file_remove, glob_paths},
+};
+
+/// Delete files from the project.
+///
+/// ## Example
+///
+/// ### Config
+///
+/// ```toml
+/// [[actions]]
+/// action = "delete_file"
+/// sources = [
+///     "src/main.rs",
+///     "*.orig"
+/// ]
+/// ```
+///
+/// ### Result
+///
+/// Deletes `src/main.rs` and all `.orig` files in the project root.
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq, Eq)]
+pub struct DeleteFileAction {
+    #[serde(flatten)]
+    pub common: Common,
+}
+
+impl ExecuteAction for DeleteFileAction {
+    fn execute(
+        &self,
+        context: &ActionContext,
+        _brick: &Brick,
+        cwd: &Path,
+    ) -> anyhow::Result<()> {
+        for source in &self.common.sources {
+            let paths = glob_paths(cwd, source)?;
+            if paths.is_empty() {
+                warn!("No files found matching '{}'", source);
+            }
+            for path in paths {
+                info!("Deleting file '{}'", path.display());
+                file_remove(context, &path)?;
+            }
+        }
+        Ok(())
+    }
+}
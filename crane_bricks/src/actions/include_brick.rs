@@ -0,0 +1,81 @@
+use std::{collections::HashMap, path::Path};
+
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    actions::{ExecuteAction, common::Common},
+    brick::{Brick, Selection},
+    context::ActionContext,
+    resolve::find_brick,
+    template::render,
+};
+
+/// Execute another brick at this point of the action list.
+///
+/// ## Example
+///
+/// ### Config
+///
+/// ```toml
+/// [[actions]]
+/// action = "include_brick"
+/// brick = "license"
+/// variant = "mit"
+/// features = ["notice"]
+/// variables = { author = "{{ company }}" }
+/// ```
+///
+/// ### Result
+///
+/// Executes the `mit` variant of the `license` brick with the `notice`
+/// feature, where the `author` variable is set to the value of `company`.
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq, Eq)]
+pub struct IncludeBrickAction {
+    #[serde(flatten)]
+    pub common: Common,
+
+    /// Name of the brick to include
+    pub brick: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variant: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<String>,
+
+    /// Variables for the included brick. Values can use the variables of
+    /// the including brick.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub variables: HashMap<String, String>,
+}
+
+impl ExecuteAction for IncludeBrickAction {
+    fn execute(
+        &self,
+        context: &ActionContext,
+        brick: &Brick,
+        cwd: &Path,
+    ) -> anyhow::Result<()> {
+        if context.is_executing(&self.brick) {
+            let mut chain = context.executing();
+            chain.push(self.brick.clone());
+            return Err(anyhow!(
+                "Brick '{}' includes itself: {}",
+                self.brick,
+                chain.join(" → ")
+            ));
+        }
+        let included = find_brick(context.bricks(), &self.brick)
+            .ok_or_else(|| anyhow!("Included brick '{}' could not be found", self.brick))?
+            .select(&Selection::new(self.variant.clone(), self.features.clone()))?;
+        let outer_variables = brick.variables(context);
+        let variables = self
+            .variables
+            .iter()
+            .map(|(name, value)| (name.clone(), render(value, &outer_variables)))
+            .collect();
+        info!("Including brick '{}'", included.name());
+        included.execute(&context.child(variables), cwd)
+    }
+}
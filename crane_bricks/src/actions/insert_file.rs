@@ -1,12 +1,17 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use serde::Deserialize;
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    actions::{ExecuteAction, common::Common},
-    brick::Brick,
+    actions::{ExecuteAction, common::Common, symlink::insert_symlink},
+    brick::{Brick, BrickFile, is_default},
     context::ActionContext,
-    file_utils::{file_append_content, file_create_new, file_replace_content},
+    file_utils::{
+        dir_create_all, file_append_content, file_create_new, file_exists,
+        file_replace_content, file_set_mode, parse_mode,
+    },
+    template::render,
 };
 
 /// Creates a new file.
@@ -21,22 +26,52 @@ use crate::{
 ///     "LICENSE"
 /// ]
 /// if_file_exists = "replace"
+/// # Optional, detected from the content by default
+/// file_type = "text" # or "binary"
+/// # Optional, copied from the brick file by default
+/// mode = "0755"
+/// # Optional, rename the file or put it into a directory (ending with /)
+/// destination = "LICENSE.txt"
+/// # Optional, insert files like `_gitignore` as `.gitignore`
+/// dotfiles = true
 /// ```
 ///
 /// ### Result
 ///
 /// Will create the LICENSE file. If it already exists, it replaces it.
-#[derive(Debug, Deserialize, Default, Clone, PartialEq, Eq)]
+/// Symlinks in the brick are created as symlinks in the target.
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq, Eq)]
 pub struct InsertFileAction {
     #[serde(flatten)]
     pub common: Common,
 
     /// Define what happens if the file already exists
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "is_default")]
     pub if_file_exists: FileExistsAction,
+
+    /// Whether the files are text or binary. By default, this is detected
+    /// from the file content.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub file_type: FileType,
+
+    /// Octal permissions for the inserted files, e.g. `"0755"`.
+    /// By default, the permissions of the brick file are copied.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+
+    /// Where the files should be inserted, relative to the target dir.
+    /// A path ending with `/` (or any path if multiple files are inserted)
+    /// is a directory, otherwise the single file is renamed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub destination: Option<String>,
+
+    /// Insert brick files starting with `_` as dotfiles,
+    /// e.g. `_gitignore` becomes `.gitignore`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub dotfiles: bool,
 }
 
-#[derive(Debug, Deserialize, Clone, Default, PartialEq, Eq)]
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum FileExistsAction {
     #[default]
@@ -45,6 +80,65 @@ pub enum FileExistsAction {
     Pass,
 }
 
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FileType {
+    #[default]
+    Auto,
+    Text,
+    Binary,
+}
+
+impl InsertFileAction {
+    /// Check if a brick file should be handled as binary
+    pub fn is_binary(&self, file: &BrickFile) -> bool {
+        match &self.file_type {
+            FileType::Auto => file.is_binary(),
+            FileType::Text => false,
+            FileType::Binary => true,
+        }
+    }
+
+    /// Name of the file in the target, with the dotfile convention applied
+    /// to the file name
+    pub fn target_name(&self, file: &BrickFile) -> String {
+        let (dir, name) = match file.name().rsplit_once('/') {
+            Some((dir, name)) => (format!("{}/", dir), name),
+            None => (String::new(), file.name()),
+        };
+        match name.strip_prefix('_') {
+            Some(name) if self.dotfiles && !name.is_empty() => {
+                format!("{}.{}", dir, name)
+            }
+            _ => file.name().to_string(),
+        }
+    }
+
+    /// Where a brick file should be inserted
+    pub fn target_path(
+        &self,
+        cwd: &Path,
+        file: &BrickFile,
+        file_count: usize,
+    ) -> PathBuf {
+        match &self.destination {
+            Some(destination) if file_count == 1 && !destination.ends_with('/') => {
+                cwd.join(destination)
+            }
+            Some(destination) => cwd.join(destination).join(self.target_name(file)),
+            None => cwd.join(self.target_name(file)),
+        }
+    }
+
+    /// The mode a brick file should have in the target
+    pub fn target_mode(&self, file: &BrickFile) -> anyhow::Result<Option<u32>> {
+        match &self.mode {
+            Some(mode) => Ok(Some(parse_mode(mode)?)),
+            None => Ok(file.mode()),
+        }
+    }
+}
+
 impl ExecuteAction for InsertFileAction {
     fn execute(
         &self,
@@ -54,7 +148,7 @@ impl ExecuteAction for InsertFileAction {
     ) -> anyhow::Result<()> {
         let mut files = brick.files();
         if !&self.common.sources.is_empty() {
-            files.retain(|file| self.common.sources.contains(&file.name().to_string()));
+            files.retain(|file| self.common.matches_source(file.name()));
         }
         debug!("{} executing for {} files", brick.name(), files.len());
         if files.len() > 1 {
@@ -71,23 +165,61 @@ impl ExecuteAction for InsertFileAction {
         } else {
             warn!("No files found to insert!");
         }
+        let variables = brick.variables(context);
+        let file_count = files.len();
         for file in files {
-            let target_path = cwd.join(file.name());
-            let content = file.content().to_string();
-            if !target_path.exists() {
-                info!("Created file '{}'", file.name());
-                file_create_new(context, &target_path, Some(content))?;
+            let target_path = self.target_path(cwd, &file, file_count);
+            let target_name = target_path.strip_prefix(cwd).unwrap_or(&target_path);
+            if let Some(parent) = target_path.parent() {
+                dir_create_all(context, parent)?;
+            }
+            if let Some(link_target) = file.link_target() {
+                insert_symlink(context, link_target, &target_path, &self.if_file_exists)?;
+                continue;
+            }
+            // Templates are only rendered in text files
+            let rendered = match file.text() {
+                Some(text) if !self.is_binary(&file) => Some(render(text, &variables)),
+                _ => None,
+            };
+            let content = rendered
+                .as_ref()
+                .map(|text| text.as_bytes())
+                .unwrap_or(file.content());
+            let mode = self.target_mode(&file)?;
+            if !file_exists(context, &target_path) {
+                info!("Created file '{}'", target_name.display());
+                file_create_new(context, &target_path, content)?;
+                if let Some(mode) = mode {
+                    file_set_mode(context, &target_path, mode)?;
+                }
                 continue;
             }
-            warn!("File '{}' already exists", file.name());
+            warn!("File '{}' already exists", target_name.display());
             match &self.if_file_exists {
                 FileExistsAction::Append => {
+                    if self.is_binary(&file) {
+                        return Err(anyhow!(
+                            "Cannot append to '{}', binary files can only be replaced",
+                            target_name.display()
+                        ));
+                    }
                     info!("Appending content to file");
-                    file_append_content(context, &target_path, &content)?
+                    file_append_content(context, &target_path, content)?;
+                    // Only an explicit mode changes the permissions of a file
+                    // the project already had
+                    if self.mode.is_some()
+                        && let Some(mode) = mode
+                    {
+                        file_set_mode(context, &target_path, mode)?;
+                    }
                 }
                 FileExistsAction::Replace => {
                     info!("Replacing all content of file");
-                    file_replace_content(context, &target_path, &content)?
+                    file_replace_content(context, &target_path, content)?;
+                    if let Some(mode) = mode {
+                        file_set_mode(context, &target_path, mode)?;
+                    }
                 }
                 FileExistsAction::Pass => {
                     info!("Continuing");
@@ -1,19 +1,38 @@
+pub mod chmod;
 pub mod common;
+pub mod copy_file;
+pub mod create_dir;
+pub mod delete_file;
+pub mod include_brick;
 pub mod insert_file;
 pub mod modify_file;
+pub mod move_file;
+pub mod patch_file;
 pub mod run_command;
+pub mod symlink;
 
-use std::path::Path;
+use std::{fmt, path::Path};
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     actions::{
-        insert_file::InsertFileAction, modify_file::ModifyFileAction,
+        chmod::ChmodAction,
+        common::Common,
+        copy_file::CopyFileAction,
+        create_dir::CreateDirAction,
+        delete_file::DeleteFileAction,
+        include_brick::IncludeBrickAction,
+        insert_file::{FileExistsAction, FileType, InsertFileAction},
+        modify_file::ModifyFileAction,
+        move_file::MoveFileAction,
+        patch_file::PatchFileAction,
         run_command::RunCommandAction,
+        symlink::SymlinkAction,
     },
     brick::Brick,
     context::ActionContext,
+    event::Event,
 };
 
 pub trait ExecuteAction {
@@ -25,12 +44,55 @@ pub trait ExecuteAction {
     ) -> anyhow::Result<()>;
 }
 
-#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
 #[serde(tag = "action", rename_all = "snake_case")]
 pub enum Action {
     InsertFile(InsertFileAction),
     ModifyFile(ModifyFileAction),
     RunCommand(RunCommandAction),
+    Chmod(ChmodAction),
+    Symlink(SymlinkAction),
+    DeleteFile(DeleteFileAction),
+    MoveFile(MoveFileAction),
+    CopyFile(CopyFileAction),
+    CreateDir(CreateDirAction),
+    IncludeBrick(IncludeBrickAction),
+    PatchFile(PatchFileAction),
+}
+
+impl Action {
+    /// Name of the action as used in the config
+    pub fn name(&self) -> &'static str {
+        match &self {
+            Action::InsertFile(_) => "insert_file",
+            Action::ModifyFile(_) => "modify_file",
+            Action::RunCommand(_) => "run_command",
+            Action::Chmod(_) => "chmod",
+            Action::Symlink(_) => "symlink",
+            Action::DeleteFile(_) => "delete_file",
+            Action::MoveFile(_) => "move_file",
+            Action::CopyFile(_) => "copy_file",
+            Action::CreateDir(_) => "create_dir",
+            Action::IncludeBrick(_) => "include_brick",
+            Action::PatchFile(_) => "patch_file",
+        }
+    }
+
+    pub fn common(&self) -> &Common {
+        match &self {
+            Action::InsertFile(action) => &action.common,
+            Action::ModifyFile(action) => &action.common,
+            Action::RunCommand(action) => &action.common,
+            Action::Chmod(action) => &action.common,
+            Action::Symlink(action) => &action.common,
+            Action::DeleteFile(action) => &action.common,
+            Action::MoveFile(action) => &action.common,
+            Action::CopyFile(action) => &action.common,
+            Action::CreateDir(action) => &action.common,
+            Action::IncludeBrick(action) => &action.common,
+            Action::PatchFile(action) => &action.common,
+        }
+    }
 }
 
 impl ExecuteAction for Action {
@@ -40,11 +102,147 @@ impl ExecuteAction for Action {
         brick: &Brick,
         cwd: &Path,
     ) -> anyhow::Result<()> {
+        if let Some(condition) = &self.common().when
+            && !condition.evaluate(cwd, &brick.variables(context))
+        {
+            info!(
+                "Skipped '{}' action, condition not met: {}",
+                self.name(),
+                condition
+            );
+            context.emit(Event::ActionSkipped {
+                brick: brick.name().to_string(),
+                action: self.name().to_string(),
+                reason: format!("condition not met: {}", condition),
+            });
+            return Ok(());
+        }
         debug!("Executing '{}' brick action '{:#?}'", brick.name(), &self);
-        match &self {
+        let result = match &self {
             Action::InsertFile(action) => action.execute(context, brick, cwd),
             Action::ModifyFile(action) => action.execute(context, brick, cwd),
             Action::RunCommand(action) => action.execute(context, brick, cwd),
+            Action::Chmod(action) => action.execute(context, brick, cwd),
+            Action::Symlink(action) => action.execute(context, brick, cwd),
+            Action::DeleteFile(action) => action.execute(context, brick, cwd),
+            Action::MoveFile(action) => action.execute(context, brick, cwd),
+            Action::CopyFile(action) => action.execute(context, brick, cwd),
+            Action::CreateDir(action) => action.execute(context, brick, cwd),
+            Action::IncludeBrick(action) => action.execute(context, brick, cwd),
+            Action::PatchFile(action) => action.execute(context, brick, cwd),
+        };
+        if result.is_ok() {
+            context.emit(Event::ActionExecuted {
+                brick: brick.name().to_string(),
+                action: self.name().to_string(),
+            });
+        }
+        result
+    }
+}
+
+/// Quoted and comma separated, e.g. `'a', 'b'`
+fn quoted(values: &[String]) -> String {
+    format!("'{}'", values.join("', '"))
+}
+
+fn if_exists(action: &FileExistsAction) -> &'static str {
+    match action {
+        FileExistsAction::Append => "append if it exists",
+        FileExistsAction::Replace => "replace if it exists",
+        FileExistsAction::Pass => "skip if it exists",
+    }
+}
+
+/// Describes the action in plain language, e.g. "Run `cargo fmt`"
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sources = &self.common().sources;
+        match self {
+            Action::InsertFile(action) => {
+                if sources.is_empty() {
+                    write!(f, "Insert all brick files")?;
+                } else {
+                    write!(f, "Insert {}", quoted(sources))?;
+                }
+                if let Some(destination) = &action.destination {
+                    write!(f, " into '{}'", destination)?;
+                }
+                write!(f, ", {}", if_exists(&action.if_file_exists))?;
+                match action.file_type {
+                    FileType::Auto => {}
+                    FileType::Text => write!(f, ", as text")?,
+                    FileType::Binary => write!(f, ", as binary")?,
+                }
+                if let Some(mode) = &action.mode {
+                    write!(f, ", with mode {}", mode)?;
+                }
+                if action.dotfiles {
+                    write!(f, ", `_` files as dotfiles")?;
+                }
+            }
+            Action::ModifyFile(action) => write!(f, "{}", action)?,
+            Action::RunCommand(action) => write!(f, "Run `{}`", action.command)?,
+            Action::Chmod(action) => {
+                write!(f, "Set mode {} of {}", action.mode, quoted(sources))?
+            }
+            Action::Symlink(action) => write!(
+                f,
+                "Link '{}' to '{}', {}",
+                action.link,
+                action.target,
+                if_exists(&action.if_file_exists)
+            )?,
+            Action::DeleteFile(_) => write!(f, "Delete {}", quoted(sources))?,
+            Action::MoveFile(action) => write!(
+                f,
+                "Move '{}' to '{}', {}",
+                action.from,
+                action.to,
+                if_exists(&action.if_file_exists)
+            )?,
+            Action::CopyFile(action) => write!(
+                f,
+                "Copy '{}' to '{}', {}",
+                action.from,
+                action.to,
+                if_exists(&action.if_file_exists)
+            )?,
+            Action::CreateDir(action) => write!(f, "Create directory '{}'", action.path)?,
+            Action::IncludeBrick(action) => {
+                write!(f, "Include brick '{}'", action.brick)?;
+                if let Some(variant) = &action.variant {
+                    write!(f, " as variant '{}'", variant)?;
+                }
+                if !action.features.is_empty() {
+                    write!(f, " with {}", quoted(&action.features))?;
+                }
+                if !action.variables.is_empty() {
+                    let mut variables: Vec<String> = action
+                        .variables
+                        .iter()
+                        .map(|(name, value)| format!("{}={}", name, value))
+                        .collect();
+                    variables.sort();
+                    write!(f, ", setting {}", variables.join(", "))?;
+                }
+            }
+            Action::PatchFile(action) => {
+                let hunks = action
+                    .patch
+                    .lines()
+                    .filter(|line| line.starts_with("@@ "))
+                    .count();
+                let plural = if hunks == 1 { "" } else { "s" };
+                write!(f, "Patch '{}' with {} hunk{}", action.path, hunks, plural)?
+            }
+        }
+        if let Some(working_dir) = &self.common().working_dir {
+            write!(f, ", in '{}'", working_dir)?;
+        }
+        if let Some(condition) = &self.common().when {
+            write!(f, ", only if {}", condition)?;
         }
+        Ok(())
     }
 }
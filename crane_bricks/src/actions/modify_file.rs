@@ -1,9 +1,12 @@
+use std::fmt;
+
 use anyhow::{Ok, anyhow};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     actions::{ExecuteAction, common::Common},
-    file_utils::{file_read_content, file_replace_content},
+    file_utils::{file_exists, file_read_content, file_replace_content},
+    template::render,
 };
 
 /// Modify a file by inserting content at a specific location.
@@ -36,7 +39,7 @@ use crate::{
 /// serde = "1"
 /// crane = "9.9.9"
 /// ```
-#[derive(Debug, Deserialize, Default, Clone, PartialEq, Eq)]
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq, Eq)]
 pub struct ModifyFileAction {
     #[serde(flatten)]
     pub common: Common,
@@ -45,16 +48,21 @@ pub struct ModifyFileAction {
     /// selector or if it should replace it.
     pub(self) r#type: ModifyType,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub content: Option<String>,
 
     /// The content selector for the modification, must be unique.
     /// Can be regex if prefix with "re:".
     pub selector: String,
+
+    /// Only modify the sources, not the files of the brick
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub only_sources: bool,
 }
 
-#[derive(Debug, Deserialize, Default, Clone, PartialEq, Eq)]
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
-enum ModifyType {
+pub enum ModifyType {
     #[default]
     Append,
     Prepend,
@@ -62,12 +70,31 @@ enum ModifyType {
 }
 
 impl ModifyFileAction {
+    /// Append `content` after the selector
+    pub fn new(selector: String, content: Option<String>) -> Self {
+        Self {
+            selector,
+            content,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_type(mut self, r#type: ModifyType) -> Self {
+        self.r#type = r#type;
+        self
+    }
+
     pub fn content(&self) -> String {
         // TODO: Get content from somewhere else if not set
         self.content.clone().unwrap_or_default()
     }
 
-    pub fn modify_content(&self, source_text: String) -> anyhow::Result<String> {
+    /// Insert `content` at all selector matches of `source_text`
+    pub fn modify_content(
+        &self,
+        source_text: String,
+        content: &str,
+    ) -> anyhow::Result<String> {
         // TODO: Handle regex
         // TODO: insert for all or just one?
 
@@ -92,13 +119,10 @@ impl ModifyFileAction {
             let modified_index = index + output.len().abs_diff(start_length);
             match &self.r#type {
                 ModifyType::Append => {
-                    output.insert_str(
-                        (modified_index + selected.len()).max(0),
-                        &self.content(),
-                    );
+                    output.insert_str(modified_index + selected.len(), content);
                 }
                 ModifyType::Prepend => {
-                    output.insert_str(modified_index, &self.content());
+                    output.insert_str(modified_index, content);
                 }
                 ModifyType::Replace => {
                     // TODO: Something isnt right here but im so tired rn pls
@@ -106,15 +130,15 @@ impl ModifyFileAction {
                     debug!(
                         "replacing from {} to {} (total chars {})",
                         modified_index,
-                        (modified_index + selected.len()).max(0),
+                        modified_index + selected.len(),
                         output.len()
                     );
                     if modified_index > output.len() {
-                        output.insert_str(output.len(), &self.content());
+                        output.insert_str(output.len(), content);
                     } else {
                         output.replace_range(
                             modified_index..(modified_index + selected.len()),
-                            &self.content(),
+                            content,
                         );
                     }
                 }
@@ -135,6 +159,41 @@ impl ModifyFileAction {
     }
 }
 
+/// Longest content that is shown in descriptions
+const SHOWN_CONTENT: usize = 40;
+
+impl fmt::Display for ModifyFileAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut content = self.content();
+        if content.chars().count() > SHOWN_CONTENT {
+            content = format!(
+                "{}…",
+                content.chars().take(SHOWN_CONTENT).collect::<String>()
+            );
+        }
+        let mut files = Vec::new();
+        if !self.only_sources {
+            files.push(String::from("the brick files"));
+        }
+        files.extend(
+            self.common
+                .sources
+                .iter()
+                .map(|source| format!("'{}'", source)),
+        );
+        match self.r#type {
+            ModifyType::Append => write!(f, "Append {:?} after", content)?,
+            ModifyType::Prepend => write!(f, "Prepend {:?} before", content)?,
+            ModifyType::Replace => write!(f, "Replace")?,
+        }
+        write!(f, " '{}'", self.selector)?;
+        if self.r#type == ModifyType::Replace {
+            write!(f, " with {:?}", content)?;
+        }
+        write!(f, " in {}", files.join(", "))
+    }
+}
+
 impl ExecuteAction for ModifyFileAction {
     fn execute(
         &self,
@@ -142,20 +201,29 @@ impl ExecuteAction for ModifyFileAction {
         brick: &crate::brick::Brick,
         cwd: &std::path::Path,
     ) -> anyhow::Result<()> {
-        let mut files: Vec<String> = brick
-            .files()
-            .iter()
-            .map(|brick_file| brick_file.name().to_string())
-            .collect();
+        let mut files: Vec<String> = Vec::new();
+        if !self.only_sources {
+            files.extend(
+                brick
+                    .files()
+                    .iter()
+                    .map(|brick_file| brick_file.name().to_string()),
+            );
+        }
         files.extend(self.common.sources.clone());
+        let content = render(&self.content(), &brick.variables(context));
         for file in files {
             let target_path = cwd.join(file);
-            if !target_path.exists() {
+            if !file_exists(context, &target_path) {
                 return Err(anyhow!("Target file does not exist!"));
             }
             info!("Modifying file '{}'", target_path.display());
-            let content = file_read_content(context, &target_path)?;
-            file_replace_content(context, &target_path, &self.modify_content(content)?)?;
+            let source_text = file_read_content(context, &target_path)?;
+            file_replace_content(
+                context,
+                &target_path,
+                self.modify_content(source_text, &content)?,
+            )?;
         }
         Ok(())
     }
@@ -0,0 +1,97 @@
+use std::path::Path;
+
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    actions::{
+        ExecuteAction,
+        common::{Common, resolve_transfers},
+        insert_file::FileExistsAction,
+    },
+    brick::{Brick, is_default},
+    context::ActionContext,
+    file_utils::{file_append_content, file_move, file_remove, is_binary, path_exists},
+};
+
+/// Move or rename files in the project.
+///
+/// ## Example
+///
+/// ### Config
+///
+/// ```toml
+/// [[actions]]
+/// action = "move_file"
+/// from = ".env.example"
+/// to = ".env"
+/// if_file_exists = "pass"
+/// ```
+///
+/// ### Result
+///
+/// Renames `.env.example` to `.env`, unless `.env` already exists.
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq, Eq)]
+pub struct MoveFileAction {
+    #[serde(flatten)]
+    pub common: Common,
+
+    /// Path or glob of the files to move
+    pub from: String,
+
+    /// New path, or a directory if `from` matches multiple files
+    pub to: String,
+
+    /// Define what happens if the destination already exists
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub if_file_exists: FileExistsAction,
+}
+
+impl ExecuteAction for MoveFileAction {
+    fn execute(
+        &self,
+        context: &ActionContext,
+        _brick: &Brick,
+        cwd: &Path,
+    ) -> anyhow::Result<()> {
+        let transfers = resolve_transfers(cwd, &self.from, &self.to)?;
+        if transfers.is_empty() {
+            warn!("No files found matching '{}'", self.from);
+        }
+        for (source, destination) in transfers {
+            info!(
+                "Moving '{}' to '{}'",
+                source.display(),
+                destination.display()
+            );
+            if !path_exists(&destination) {
+                file_move(context, &source, &destination)?;
+                continue;
+            }
+            warn!("File '{}' already exists", destination.display());
+            match &self.if_file_exists {
+                FileExistsAction::Append => {
+                    let content = std::fs::read(&source)?;
+                    if is_binary(&content) {
+                        return Err(anyhow!(
+                            "Cannot append '{}', binary files can only be replaced",
+                            source.display()
+                        ));
+                    }
+                    info!("Appending content to file");
+                    file_append_content(context, &destination, content)?;
+                    file_remove(context, &source)?;
+                }
+                FileExistsAction::Replace => {
+                    info!("Replacing file");
+                    file_remove(context, &destination)?;
+                    file_move(context, &source, &destination)?;
+                }
+                FileExistsAction::Pass => {
+                    info!("Continuing");
+                }
+            }
+        }
+        Ok(())
+    }
+}
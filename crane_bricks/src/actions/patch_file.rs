@@ -0,0 +1,170 @@
+use std::path::Path;
+
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    actions::{ExecuteAction, common::Common},
+    brick::Brick,
+    context::ActionContext,
+    file_utils::{file_exists, file_read_content, file_replace_content},
+    template::render,
+};
+
+/// Apply the hunks of a unified diff to a file of the project.
+///
+/// Hunks are looked up by their content, so they still apply if lines were
+/// added or removed elsewhere in the file.
+///
+/// ## Example
+///
+/// ### Config
+///
+/// ```toml
+/// [[actions]]
+/// action = "patch_file"
+/// path = "src/main.rs"
+/// patch = """
+/// @@ -1,3 +1,3 @@
+///  fn main() {
+/// -    println!("Hello, world!");
+/// +    println!("Hello, crane!");
+///  }
+/// """
+/// ```
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq, Eq)]
+pub struct PatchFileAction {
+    #[serde(flatten)]
+    pub common: Common,
+
+    /// Path of the file to patch, relative to the target dir
+    pub path: String,
+
+    /// The hunks of a unified diff, the `---` and `+++` headers are optional
+    pub patch: String,
+}
+
+/// A change to a consecutive block of lines
+#[derive(Debug, Default)]
+pub(crate) struct Hunk {
+    /// Line in the original file where the hunk starts, counting from 0
+    start: usize,
+    old: Vec<String>,
+    new: Vec<String>,
+}
+
+impl PatchFileAction {
+    /// Apply the patch to `text`
+    pub fn apply(&self, text: &str, patch: &str) -> anyhow::Result<String> {
+        let mut lines: Vec<String> =
+            text.split_inclusive('\n').map(String::from).collect();
+        // Lines added or removed by the previous hunks
+        let mut offset: isize = 0;
+        for (index, hunk) in parse_hunks(patch)?.into_iter().enumerate() {
+            let expected = hunk.start.saturating_add_signed(offset);
+            let position = find_lines(&lines, &hunk.old, expected).ok_or_else(|| {
+                anyhow!(
+                    "Hunk {} doesn't apply to '{}', the file has changed too much",
+                    index + 1,
+                    self.path
+                )
+            })?;
+            lines.splice(position..position + hunk.old.len(), hunk.new.clone());
+            offset += hunk.new.len() as isize - hunk.old.len() as isize;
+            offset += position as isize - expected as isize;
+        }
+        Ok(lines.concat())
+    }
+}
+
+/// Position of `needle` in `lines`, the closest one to `expected` if it
+/// appears multiple times
+fn find_lines(lines: &[String], needle: &[String], expected: usize) -> Option<usize> {
+    if needle.is_empty() {
+        return Some(expected.min(lines.len()));
+    }
+    let matches = |position: usize| lines[position..].starts_with(needle);
+    let last = lines.len().checked_sub(needle.len())?;
+    (0..=last)
+        .filter(|position| matches(*position))
+        .min_by_key(|position| position.abs_diff(expected))
+}
+
+pub(crate) fn parse_hunks(patch: &str) -> anyhow::Result<Vec<Hunk>> {
+    let mut hunks: Vec<Hunk> = Vec::new();
+    // Kind of the previous line, which "\ No newline at end of file" refers to
+    let mut previous = ' ';
+    for line in patch.lines() {
+        if let Some(header) = line.strip_prefix("@@ ") {
+            hunks.push(Hunk {
+                start: parse_start(header)
+                    .ok_or_else(|| anyhow!("Invalid hunk header '{}'", line))?,
+                ..Default::default()
+            });
+            continue;
+        }
+        let Some(hunk) = hunks.last_mut() else {
+            // Everything before the first hunk, like the file headers
+            continue;
+        };
+        if line.starts_with('\\') {
+            if previous != '+'
+                && let Some(last) = hunk.old.last_mut()
+            {
+                last.pop();
+            }
+            if previous != '-'
+                && let Some(last) = hunk.new.last_mut()
+            {
+                last.pop();
+            }
+            continue;
+        }
+        let text = format!("{}\n", line.get(1..).unwrap_or_default());
+        previous = line.chars().next().unwrap_or(' ');
+        match previous {
+            '-' => hunk.old.push(text),
+            '+' => hunk.new.push(text),
+            // Empty lines are context lines whose space was trimmed
+            ' ' => {
+                hunk.old.push(text.clone());
+                hunk.new.push(text);
+            }
+            _ => return Err(anyhow!("Invalid patch line '{}'", line)),
+        }
+    }
+    if hunks.is_empty() {
+        return Err(anyhow!("The patch has no hunks"));
+    }
+    Ok(hunks)
+}
+
+/// Start line of the original file from a hunk header like `-1,3 +1,4 @@`
+fn parse_start(header: &str) -> Option<usize> {
+    let old = header.split_whitespace().next()?.strip_prefix('-')?;
+    let (start, count) = old.split_once(',').unwrap_or((old, "1"));
+    let start: usize = start.parse().ok()?;
+    // Hunks that only add lines name the line they are added after
+    if count == "0" {
+        return Some(start);
+    }
+    Some(start.saturating_sub(1))
+}
+
+impl ExecuteAction for PatchFileAction {
+    fn execute(
+        &self,
+        context: &ActionContext,
+        brick: &Brick,
+        cwd: &Path,
+    ) -> anyhow::Result<()> {
+        let target_path = cwd.join(&self.path);
+        if !file_exists(context, &target_path) {
+            return Err(anyhow!("Target file '{}' does not exist!", self.path));
+        }
+        info!("Patching file '{}'", self.path);
+        let patch = render(&self.patch, &brick.variables(context));
+        let text = file_read_content(context, &target_path)?;
+        file_replace_content(context, &target_path, self.apply(&text, &patch)?)
+    }
+}
@@ -1,11 +1,12 @@
 use std::{path::Path, process::Command};
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     actions::{ExecuteAction, common::Common},
     brick::Brick,
     context::ActionContext,
+    event::Event,
 };
 
 /// Run a command
@@ -22,7 +23,7 @@ use crate::{
 /// ### Result
 ///
 /// Will run echo hi and write the stdout into test.txt
-#[derive(Debug, Deserialize, Default, Clone, PartialEq, Eq)]
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq, Eq)]
 pub struct RunCommandAction {
     #[serde(flatten)]
     pub common: Common,
@@ -38,6 +39,10 @@ impl ExecuteAction for RunCommandAction {
         cwd: &Path,
     ) -> anyhow::Result<()> {
         info!("Running command");
+        context.emit(Event::CommandRun {
+            command: self.command.clone(),
+            dry_run: context.dry_run,
+        });
         if context.dry_run {
             return Ok(());
         }
@@ -0,0 +1,111 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    actions::{ExecuteAction, common::Common, insert_file::FileExistsAction},
+    brick::{Brick, is_default},
+    context::ActionContext,
+    file_utils::{file_remove, path_exists, symlink_create},
+};
+
+/// Create a symlink in the project.
+///
+/// ## Example
+///
+/// ### Config
+///
+/// ```toml
+/// [[actions]]
+/// action = "symlink"
+/// target = "../../rustfmt.toml"
+/// link = "rustfmt.toml"
+/// if_file_exists = "replace"
+/// ```
+///
+/// ### Result
+///
+/// Creates `rustfmt.toml` which points to `../../rustfmt.toml`.
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq, Eq)]
+pub struct SymlinkAction {
+    #[serde(flatten)]
+    pub common: Common,
+
+    /// Where the link points to, relative to the link itself
+    pub target: String,
+
+    /// Path of the link that will be created
+    pub link: String,
+
+    /// Define what happens if the link path already exists
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub if_file_exists: FileExistsAction,
+}
+
+impl ExecuteAction for SymlinkAction {
+    fn execute(
+        &self,
+        context: &ActionContext,
+        _brick: &Brick,
+        cwd: &Path,
+    ) -> anyhow::Result<()> {
+        insert_symlink(
+            context,
+            Path::new(&self.target),
+            &cwd.join(&self.link),
+            &self.if_file_exists,
+        )
+    }
+}
+
+/// Create a symlink, respecting what should happen if the path is taken.
+///
+/// Symlinks can't be appended to, so `append` only succeeds if the existing
+/// link already points to the same target.
+pub(crate) fn insert_symlink(
+    context: &ActionContext,
+    target: &Path,
+    link: &Path,
+    if_file_exists: &FileExistsAction,
+) -> anyhow::Result<()> {
+    if !path_exists(link) {
+        info!(
+            "Created symlink '{}' -> '{}'",
+            link.display(),
+            target.display()
+        );
+        return symlink_create(context, target, link);
+    }
+    warn!("File '{}' already exists", link.display());
+    match if_file_exists {
+        FileExistsAction::Append => {
+            if fs::read_link(link).ok() == Some(PathBuf::from(target)) {
+                info!("Symlink already points to '{}'", target.display());
+                return Ok(());
+            }
+            Err(anyhow!(
+                "Cannot append to '{}', symlinks can only be replaced",
+                link.display()
+            ))
+        }
+        FileExistsAction::Replace => {
+            if fs::symlink_metadata(link)?.is_dir() {
+                return Err(anyhow!(
+                    "Cannot replace directory '{}' with a symlink",
+                    link.display()
+                ));
+            }
+            info!("Replacing '{}' with symlink", link.display());
+            file_remove(context, link)?;
+            symlink_create(context, target, link)
+        }
+        FileExistsAction::Pass => {
+            info!("Continuing");
+            Ok(())
+        }
+    }
+}
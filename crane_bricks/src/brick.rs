@@ -1,76 +1,542 @@
 use std::{
+    collections::HashMap,
     fs,
     path::{Path, PathBuf},
 };
 
 use anyhow::anyhow;
-use serde::Deserialize;
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Deserializer, Serialize};
 
 use crate::{
     actions::{Action, ExecuteAction, insert_file::InsertFileAction},
     context::ActionContext,
-    file_utils::{sub_dirs, sub_paths},
+    event::Event,
+    file_utils::{SKIPPED_DIRS, file_mode, is_binary, sub_dirs, sub_paths},
+    resolve::find_brick,
+    validate::config_error,
 };
 
-const BRICK_CONFIG_FILE: &str = "brick.toml";
+pub const BRICK_CONFIG_FILE: &str = "brick.toml";
 
-#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
 pub struct BrickConfig {
     name: String,
 
-    #[serde(default)]
+    /// Short summary shown when picking bricks
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+
+    /// Keywords to find the brick
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    authors: Vec<String>,
+
+    /// Version of the brick itself, only informational
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    homepage: Option<String>,
+
+    /// Oldest crane version that can execute the brick, e.g. `"0.3"`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    min_crane_version: Option<String>,
+
+    /// Name of a brick this brick is based on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    extends: Option<String>,
+
+    /// How the actions of this brick are combined with the ones of the
+    /// brick it extends
+    #[serde(default, skip_serializing_if = "is_default")]
+    extend_actions: ExtendActions,
+
+    /// Names of bricks that must be executed before this one
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    requires: Vec<String>,
+
+    /// Names of bricks or capabilities this brick can't be combined with
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    conflicts: Vec<String>,
+
+    /// Capabilities of the brick, e.g. `"license"`. Two bricks that provide
+    /// the same capability can't be combined.
+    #[serde(
+        default,
+        deserialize_with = "one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    provides: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    variables: Vec<Variable>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     actions: Vec<Action>,
+
+    /// Mutually exclusive versions of the brick, one of them is applied
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    variants: Vec<BrickPart>,
+
+    /// Variant that is used if none is selected
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    default_variant: Option<String>,
+
+    /// Optional additions to the brick, any of them can be applied
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    features: Vec<BrickPart>,
+}
+
+/// Summary of a brick for machine-readable output
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct BrickInfo {
+    pub name: String,
+    pub path: PathBuf,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    pub authors: Vec<String>,
+    pub version: Option<String>,
+    pub homepage: Option<String>,
+    pub min_crane_version: Option<String>,
+    pub extends: Option<String>,
+    pub requires: Vec<String>,
+    pub conflicts: Vec<String>,
+    pub provides: Vec<String>,
+    pub variants: Vec<String>,
+    pub features: Vec<String>,
+}
+
+/// A variant or feature of a brick, which is only applied if selected
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct BrickPart {
+    name: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+
+    /// Brick files that are only inserted if this part is selected
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    files: Vec<String>,
+
+    /// Actions that run after the actions of the brick if selected
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    actions: Vec<Action>,
+}
+
+impl BrickPart {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn files(&self) -> &[String] {
+        &self.files
+    }
+
+    pub fn actions(&self) -> &[Action] {
+        &self.actions
+    }
+}
+
+/// The variant and features selected when adding a brick
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Selection {
+    pub variant: Option<String>,
+    pub features: Vec<String>,
+}
+
+impl Selection {
+    pub fn new(variant: Option<String>, features: Vec<String>) -> Self {
+        Self { variant, features }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExtendActions {
+    /// Run the actions of the base brick first, then the own ones
+    #[default]
+    Append,
+    /// Run the own actions first, then the ones of the base brick
+    Prepend,
+    /// Only run the own actions
+    Replace,
+}
+
+/// A variable that can be used in templates and conditions
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Variable {
+    name: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    default: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+}
+
+impl Variable {
+    pub fn new(
+        name: String,
+        default: Option<String>,
+        description: Option<String>,
+    ) -> Self {
+        Self {
+            name,
+            default,
+            description,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn default(&self) -> Option<&str> {
+        self.default.as_deref()
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
 }
 
 impl BrickConfig {
     pub fn new(name: String, actions: Vec<Action>) -> Self {
-        Self { name, actions }
+        Self {
+            name,
+            actions,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_variables(mut self, variables: Vec<Variable>) -> Self {
+        self.variables = variables;
+        self
     }
 
     pub fn name(&self) -> &str {
         &self.name
     }
 
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    pub fn authors(&self) -> &[String] {
+        &self.authors
+    }
+
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    pub fn homepage(&self) -> Option<&str> {
+        self.homepage.as_deref()
+    }
+
+    pub fn min_crane_version(&self) -> Option<&str> {
+        self.min_crane_version.as_deref()
+    }
+
+    /// Check that the brick can be executed by the given crane version
+    pub fn check_crane_version(&self, crane_version: &str) -> anyhow::Result<()> {
+        match &self.min_crane_version {
+            Some(min_version) => {
+                check_min_version(&self.name, min_version, crane_version)
+            }
+            None => Ok(()),
+        }
+    }
+
     pub fn actions(&self) -> &[Action] {
         &self.actions
     }
+
+    pub fn variables(&self) -> &[Variable] {
+        &self.variables
+    }
+
+    pub fn requires(&self) -> &[String] {
+        &self.requires
+    }
+
+    pub fn conflicts(&self) -> &[String] {
+        &self.conflicts
+    }
+
+    pub fn provides(&self) -> &[String] {
+        &self.provides
+    }
+
+    pub fn extends(&self) -> Option<&str> {
+        self.extends.as_deref()
+    }
+
+    pub fn variants(&self) -> &[BrickPart] {
+        &self.variants
+    }
+
+    pub fn default_variant(&self) -> Option<&str> {
+        self.default_variant.as_deref()
+    }
+
+    pub fn features(&self) -> &[BrickPart] {
+        &self.features
+    }
+
+    pub fn variant(&self, name: &str) -> Option<&BrickPart> {
+        find_part(&self.variants, name)
+    }
+
+    pub fn feature(&self, name: &str) -> Option<&BrickPart> {
+        find_part(&self.features, name)
+    }
+
+    /// Combine this config with the config of the brick it extends
+    fn inherit(&mut self, base: &BrickConfig) {
+        self.actions = match self.extend_actions {
+            ExtendActions::Append => {
+                [base.actions.clone(), self.actions.clone()].concat()
+            }
+            ExtendActions::Prepend => {
+                [self.actions.clone(), base.actions.clone()].concat()
+            }
+            ExtendActions::Replace => self.actions.clone(),
+        };
+        let mut variables = base.variables.clone();
+        for variable in &self.variables {
+            variables.retain(|other| other.name != variable.name);
+            variables.push(variable.clone());
+        }
+        self.variables = variables;
+        for (own, inherited) in [
+            (&mut self.variants, &base.variants),
+            (&mut self.features, &base.features),
+        ] {
+            for part in inherited {
+                if find_part(own, &part.name).is_none() {
+                    own.push(part.clone());
+                }
+            }
+        }
+        if self.default_variant.is_none() {
+            self.default_variant = base.default_variant.clone();
+        }
+        // The actions of the base need its crane version as well
+        self.min_crane_version = match (&self.min_crane_version, &base.min_crane_version)
+        {
+            (Some(own), Some(inherited))
+                if parse_version(inherited) > parse_version(own) =>
+            {
+                Some(inherited.clone())
+            }
+            (own, inherited) => own.clone().or_else(|| inherited.clone()),
+        };
+        for (own, inherited) in [
+            (&mut self.requires, &base.requires),
+            (&mut self.conflicts, &base.conflicts),
+            (&mut self.provides, &base.provides),
+        ] {
+            for value in inherited {
+                if !own.contains(value) {
+                    own.push(value.clone());
+                }
+            }
+        }
+    }
+}
+
+/// Check that the brick in `dir` can be executed by the given crane version.
+///
+/// Only `min_crane_version` is read from its config, so this also works for
+/// bricks that use actions or keys this version doesn't know and can't load.
+pub fn check_crane_version_in(dir: &Path, crane_version: &str) -> anyhow::Result<()> {
+    let Ok(text) = fs::read_to_string(dir.join(BRICK_CONFIG_FILE)) else {
+        return Ok(());
+    };
+    let Ok(table) = text.parse::<toml::Table>() else {
+        return Ok(());
+    };
+    let Some(min_version) = table
+        .get("min_crane_version")
+        .and_then(|value| value.as_str())
+    else {
+        return Ok(());
+    };
+    let name = match table.get("name").and_then(|value| value.as_str()) {
+        Some(name) => name.to_string(),
+        None => dir
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default(),
+    };
+    check_min_version(&name, min_version, crane_version)
+}
+
+/// Parse a version that may leave out the minor and patch version, like `0.3`
+fn parse_version(version: &str) -> Option<Version> {
+    let missing = 3_usize.saturating_sub(version.split('.').count());
+    Version::parse(&format!("{}{}", version, ".0".repeat(missing))).ok()
+}
+
+fn check_min_version(
+    name: &str,
+    min_version: &str,
+    crane_version: &str,
+) -> anyhow::Result<()> {
+    let requirement =
+        VersionReq::parse(&format!(">={}", min_version)).map_err(|error| {
+            anyhow!(
+                "Brick '{}' has an invalid min_crane_version '{}': {}",
+                name,
+                min_version,
+                error
+            )
+        })?;
+    let version = Version::parse(crane_version)?;
+    if !requirement.matches(&version) {
+        return Err(anyhow!(
+            "Brick '{}' needs crane {} or newer, but this is crane {}. \
+             Please upgrade crane to use it.",
+            name,
+            min_version,
+            crane_version
+        ));
+    }
+    Ok(())
+}
+
+fn find_part<'a>(parts: &'a [BrickPart], name: &str) -> Option<&'a BrickPart> {
+    parts
+        .iter()
+        .find(|part| part.name.to_lowercase() == name.to_lowercase())
+}
+
+/// Whether a value is its default, to leave it out when serializing
+pub(crate) fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
+/// Deserialize either a single string or a list of strings
+fn one_or_many<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(value) => vec![value],
+        OneOrMany::Many(values) => values,
+    })
 }
 
 #[derive(Debug, Clone)]
 pub struct BrickFile {
     name: String,
-    content: String,
+    content: Vec<u8>,
+    mode: Option<u32>,
+    link_target: Option<PathBuf>,
 }
 
 impl BrickFile {
-    pub fn new(name: String, content: String) -> Self {
-        Self { name, content }
+    pub fn new(name: String, content: Vec<u8>) -> Self {
+        Self {
+            name,
+            content,
+            mode: None,
+            link_target: None,
+        }
+    }
+
+    /// A brick file that is a symlink pointing to `target`
+    pub fn new_symlink(name: String, target: PathBuf) -> Self {
+        Self {
+            name,
+            content: Vec::new(),
+            mode: None,
+            link_target: Some(target),
+        }
+    }
+
+    pub fn with_mode(mut self, mode: Option<u32>) -> Self {
+        self.mode = mode;
+        self
     }
 
     pub fn name(&self) -> &str {
         &self.name
     }
 
-    pub fn content(&self) -> &str {
+    /// Raw bytes of the file
+    pub fn content(&self) -> &[u8] {
         &self.content
     }
+
+    /// Returns the content as text, or `None` if the file looks binary
+    pub fn text(&self) -> Option<&str> {
+        if is_binary(&self.content) {
+            return None;
+        }
+        str::from_utf8(&self.content).ok()
+    }
+
+    pub fn is_binary(&self) -> bool {
+        is_binary(&self.content)
+    }
+
+    /// Unix permission bits of the source file, if available
+    pub fn mode(&self) -> Option<u32> {
+        self.mode
+    }
+
+    /// Where the symlink points to, `None` if this is a regular file
+    pub fn link_target(&self) -> Option<&Path> {
+        self.link_target.as_deref()
+    }
+
+    pub fn is_symlink(&self) -> bool {
+        self.link_target.is_some()
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct Brick {
     config: BrickConfig,
     source_path: PathBuf,
+
+    /// Source paths of the bricks this brick extends, the root base first
+    base_paths: Vec<PathBuf>,
+
+    /// The selected variant and features
+    selection: Selection,
 }
 
 impl Brick {
     pub fn new(name: String, source_path: PathBuf) -> Self {
         Brick {
-            config: BrickConfig {
+            config: BrickConfig::new(
                 name,
                 // If no action is configured, InsertFileAction is default
-                actions: vec![Action::InsertFile(InsertFileAction::default())],
-            },
+                vec![Action::InsertFile(InsertFileAction::default())],
+            ),
             source_path,
+            base_paths: Vec::new(),
+            selection: Selection::default(),
         }
     }
 
@@ -78,9 +544,91 @@ impl Brick {
         Brick {
             config,
             source_path,
+            base_paths: Vec::new(),
+            selection: Selection::default(),
         }
     }
 
+    /// Create the brick at the path without resolving what it extends
+    fn load(path: PathBuf) -> anyhow::Result<Self> {
+        let config_file = path.join(BRICK_CONFIG_FILE);
+        if !config_file.exists() {
+            debug!(
+                "Brick config file not found at '{:?}'",
+                config_file.display()
+            );
+            let name = path
+                .as_path()
+                .file_name()
+                .ok_or_else(|| anyhow!("Could not read brick dir name!"))?;
+            return Ok(Brick::new(name.display().to_string(), path));
+        }
+        debug!("Creating Brick from config file");
+        let text = fs::read_to_string(&config_file)?;
+        let config: BrickConfig = toml::from_str(&text)
+            .map_err(|error| config_error(&config_file, &text, &error))?;
+        Ok(Brick::new_with_config(config, path))
+    }
+
+    /// Resolve the chain of bricks this brick extends from `bricks`.
+    ///
+    /// The resolved brick inherits the files and actions of its bases.
+    pub fn resolve_extends(&self, bricks: &[Brick]) -> anyhow::Result<Brick> {
+        let mut chain: Vec<&Brick> = vec![self];
+        let mut current = self;
+        while let Some(base_name) = current.config.extends() {
+            let base = find_brick(bricks, base_name).ok_or_else(|| {
+                anyhow!(
+                    "Brick '{}' extends '{}', which could not be found",
+                    current.name(),
+                    base_name
+                )
+            })?;
+            if chain.iter().any(|brick| brick.name() == base.name()) {
+                let names: Vec<&str> = chain.iter().map(|brick| brick.name()).collect();
+                return Err(anyhow!(
+                    "Brick '{}' extends itself: {} → {}",
+                    self.name(),
+                    names.join(" → "),
+                    base.name()
+                ));
+            }
+            chain.push(base);
+            current = base;
+        }
+
+        // Walk from the root base to this brick, so every brick inherits its
+        // base as already resolved with its own `extend_actions`
+        let mut resolved = chain[chain.len() - 1].clone();
+        for brick in chain.iter().rev().skip(1) {
+            let mut next = (*brick).clone();
+            next.config.inherit(&resolved.config);
+            next.base_paths = resolved.base_paths.clone();
+            next.base_paths.push(resolved.source_path.clone());
+            resolved = next;
+        }
+        Ok(resolved)
+    }
+
+    /// Resolve what this brick extends from the bricks in the same brick
+    /// directory
+    pub fn resolve_siblings(self) -> anyhow::Result<Brick> {
+        if self.config.extends().is_none() {
+            return Ok(self);
+        }
+        // The base brick has to be in the same brick directory
+        let siblings: Vec<Brick> = self
+            .source_path
+            .parent()
+            .and_then(|parent| sub_dirs(parent).ok())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|dir| dir != &self.source_path)
+            .filter_map(|dir| Brick::load(dir).ok())
+            .collect();
+        self.resolve_extends(&siblings)
+    }
+
     pub fn name(&self) -> &str {
         &self.config.name
     }
@@ -93,73 +641,298 @@ impl Brick {
         &self.config
     }
 
-    pub fn execute(&self, context: &ActionContext, cwd: &Path) -> anyhow::Result<()> {
-        for action in &self.config.actions {
-            action.execute(context, self, cwd)?;
+    pub fn info(&self) -> BrickInfo {
+        let config = &self.config;
+        let names =
+            |parts: &[BrickPart]| parts.iter().map(|part| part.name.clone()).collect();
+        BrickInfo {
+            name: config.name.clone(),
+            path: self.source_path.clone(),
+            description: config.description.clone(),
+            tags: config.tags.clone(),
+            authors: config.authors.clone(),
+            version: config.version.clone(),
+            homepage: config.homepage.clone(),
+            min_crane_version: config.min_crane_version.clone(),
+            extends: config.extends.clone(),
+            requires: config.requires.clone(),
+            conflicts: config.conflicts.clone(),
+            provides: config.provides.clone(),
+            variants: names(&config.variants),
+            features: names(&config.features),
         }
-        Ok(())
     }
 
-    /// Returns a list of all files that
-    pub fn files(&self) -> Vec<BrickFile> {
-        let Ok(paths) = sub_paths(self.path()) else {
-            return vec![];
+    /// Source paths of the bricks this brick extends, the root base first
+    pub fn base_paths(&self) -> &[PathBuf] {
+        &self.base_paths
+    }
+
+    pub fn selection(&self) -> &Selection {
+        &self.selection
+    }
+
+    /// Select a variant and features of the brick.
+    ///
+    /// If the brick has variants and none is selected, the default variant is
+    /// used. Fails if the selection doesn't exist for this brick.
+    pub fn select(&self, selection: &Selection) -> anyhow::Result<Brick> {
+        let variant = match (&selection.variant, self.config.default_variant()) {
+            (Some(variant), _) => Some(variant.as_str()),
+            (None, default) => default,
+        };
+        let variant_names = || {
+            self.config
+                .variants
+                .iter()
+                .map(|variant| variant.name.as_str())
+                .collect::<Vec<&str>>()
+                .join(", ")
+        };
+        let variant = match variant {
+            Some(variant) => {
+                let part = self.config.variant(variant).ok_or_else(|| {
+                    if self.config.variants.is_empty() {
+                        anyhow!("Brick '{}' has no variants", self.name())
+                    } else {
+                        anyhow!(
+                            "Brick '{}' has no variant '{}', choose one of: {}",
+                            self.name(),
+                            variant,
+                            variant_names()
+                        )
+                    }
+                })?;
+                Some(part.name.clone())
+            }
+            None if !self.config.variants.is_empty() => {
+                return Err(anyhow!(
+                    "Brick '{}' has variants, choose one of: {} (e.g. '{}:{}')",
+                    self.name(),
+                    variant_names(),
+                    self.name(),
+                    self.config.variants[0].name
+                ));
+            }
+            None => None,
         };
-        paths
+        let mut features = Vec::new();
+        for feature in &selection.features {
+            let part = self.config.feature(feature).ok_or_else(|| {
+                anyhow!("Brick '{}' has no feature '{}'", self.name(), feature)
+            })?;
+            if !features.contains(&part.name) {
+                features.push(part.name.clone());
+            }
+        }
+        let mut brick = self.clone();
+        brick.selection = Selection::new(variant, features);
+        Ok(brick)
+    }
+
+    /// The variant and features that are selected
+    fn selected_parts(&self) -> Vec<&BrickPart> {
+        let variant = self
+            .selection
+            .variant
+            .as_deref()
+            .and_then(|name| self.config.variant(name));
+        let features = self
+            .selection
+            .features
             .iter()
-            .filter_map(|path| {
-                let name = path.file_name()?.display().to_string();
-                if !path.is_file() || name == BRICK_CONFIG_FILE {
-                    return None;
-                }
-                let content = fs::read_to_string(path).unwrap_or_default();
+            .filter_map(|name| self.config.feature(name));
+        variant.into_iter().chain(features).collect()
+    }
 
-                Some(BrickFile::new(name, content))
-            })
+    /// All actions that run, including the ones of the selected variant and
+    /// features
+    pub fn actions(&self) -> Vec<&Action> {
+        self.config
+            .actions
+            .iter()
+            .chain(
+                self.selected_parts()
+                    .into_iter()
+                    .flat_map(|part| part.actions.iter()),
+            )
             .collect()
     }
+
+    /// The variables for executing this brick: the defaults of the brick,
+    /// overwritten by the variables set in the context
+    pub fn variables(&self, context: &ActionContext) -> HashMap<String, String> {
+        let mut variables: HashMap<String, String> = self
+            .config
+            .variables
+            .iter()
+            .filter_map(|variable| {
+                Some((variable.name.clone(), variable.default.clone()?))
+            })
+            .collect();
+        variables.extend(context.variables.clone());
+        variables
+    }
+
+    /// Execute all actions of the brick. If one fails, the changes made by
+    /// the previous actions are rolled back.
+    pub fn execute(&self, context: &ActionContext, cwd: &Path) -> anyhow::Result<()> {
+        let checkpoint = context.checkpoint();
+        context.emit(Event::BrickStarted {
+            brick: self.name().to_string(),
+        });
+        context.enter(self.name());
+        let result = self
+            .actions()
+            .into_iter()
+            .try_for_each(|action| action.execute(context, self, cwd));
+        context.leave();
+        if result.is_err() {
+            context.rollback_to(checkpoint);
+            context.emit(Event::RolledBack {
+                brick: self.name().to_string(),
+            });
+        }
+        context.emit(Event::BrickFinished {
+            brick: self.name().to_string(),
+            error: result.as_ref().err().map(|error| error.to_string()),
+        });
+        result
+    }
+
+    /// Returns a list of all files that belong to the brick, including the
+    /// files of the bricks it extends unless it has a file with the same name
+    ///
+    /// Files of variants and features are only included if they are selected.
+    pub fn files(&self) -> Vec<BrickFile> {
+        let selected: Vec<&String> = self
+            .selected_parts()
+            .into_iter()
+            .flat_map(|part| part.files.iter())
+            .collect();
+        let excluded: Vec<&String> = self
+            .config
+            .variants
+            .iter()
+            .chain(&self.config.features)
+            .flat_map(|part| part.files.iter())
+            .filter(|file| !selected.contains(file))
+            .collect();
+        let mut files = self.all_files();
+        files.retain(|file| !excluded.iter().any(|name| *name == file.name()));
+        files
+    }
+
+    /// Like [`Self::files`], but includes the files of all variants and
+    /// features
+    pub fn all_files(&self) -> Vec<BrickFile> {
+        let mut files: Vec<BrickFile> = Vec::new();
+        for path in self.base_paths.iter().chain([&self.source_path]) {
+            for file in files_in_dir(path) {
+                files.retain(|other| other.name() != file.name());
+                files.push(file);
+            }
+        }
+        files
+    }
+}
+
+/// All files of a single brick directory, without the config file.
+///
+/// Files in sub directories are named by their path relative to the brick,
+/// e.g. `src/main.rs`. Directories in [`SKIPPED_DIRS`] are left out.
+fn files_in_dir(dir: &Path) -> Vec<BrickFile> {
+    files_in_sub_dir(dir, "")
+}
+
+fn files_in_sub_dir(dir: &Path, prefix: &str) -> Vec<BrickFile> {
+    let Ok(paths) = sub_paths(dir) else {
+        return vec![];
+    };
+    let mut files = Vec::new();
+    for path in paths {
+        let Some(file_name) = path.file_name() else {
+            continue;
+        };
+        let name = format!("{}{}", prefix, file_name.display());
+        if name == BRICK_CONFIG_FILE {
+            continue;
+        }
+        // Symlinks are kept as they are instead of being followed
+        let Ok(metadata) = fs::symlink_metadata(&path) else {
+            continue;
+        };
+        if metadata.is_symlink() {
+            match fs::read_link(&path) {
+                Ok(target) => files.push(BrickFile::new_symlink(name, target)),
+                Err(error) => {
+                    warn!("Failed to read brick symlink '{}': {}", name, error)
+                }
+            }
+            continue;
+        }
+        if metadata.is_dir() {
+            if !SKIPPED_DIRS.iter().any(|skipped| file_name == *skipped) {
+                files.extend(files_in_sub_dir(&path, &format!("{}/", name)));
+            }
+            continue;
+        }
+        if !metadata.is_file() {
+            continue;
+        }
+        match fs::read(&path) {
+            Ok(content) => {
+                files.push(BrickFile::new(name, content).with_mode(file_mode(&path)))
+            }
+            Err(error) => warn!("Failed to read brick file '{}': {}", name, error),
+        }
+    }
+    files
 }
 
 impl TryFrom<PathBuf> for Brick {
     type Error = anyhow::Error;
 
     fn try_from(value: PathBuf) -> Result<Self, Self::Error> {
-        let config_file = value.join(BRICK_CONFIG_FILE);
-        if !config_file.exists() {
-            debug!(
-                "Brick config file not found at '{:?}'",
-                config_file.display()
-            );
-            let name = value
-                .as_path()
-                .file_name()
-                .ok_or_else(|| anyhow!("Could not read brick dir name!"))?;
-            return Ok(Brick::new(name.display().to_string(), value));
-        }
-        debug!("Creating Brick from config file");
-        let config: BrickConfig =
-            toml::from_str(fs::read_to_string(config_file)?.as_str())?;
-        Ok(Brick::new_with_config(config, value))
+        Brick::load(value)?.resolve_siblings()
     }
 }
 
 /// Get all bricks in a directory
 pub fn bricks_in_dir(dir: &Path) -> Vec<Brick> {
+    let (bricks, failed) = try_bricks_in_dir(dir);
+    for (path, error) in failed {
+        warn!(
+            "Failed to create brick at '{}'. Error: {}",
+            path.display(),
+            error
+        );
+    }
+    bricks
+}
+
+/// Get all bricks in a directory, and the directories that failed to load
+/// as bricks with the reason
+pub fn try_bricks_in_dir(dir: &Path) -> (Vec<Brick>, Vec<(PathBuf, anyhow::Error)>) {
     debug!("{:#?}", sub_dirs(dir));
     let Ok(dirs) = sub_dirs(dir) else {
-        return vec![];
+        return (vec![], vec![]);
     };
-    dirs.iter()
-        .filter_map(|dir| match Brick::try_from(dir.clone()) {
-            Ok(brick) => Some(brick),
-            Err(error) => {
-                warn!(
-                    "Failed to create brick at '{}'. Error: {}",
-                    dir.display(),
-                    error
-                );
-                None
-            }
-        })
-        .collect::<Vec<Brick>>()
+    let mut failed = Vec::new();
+    let mut bricks: Vec<(PathBuf, Brick)> = Vec::new();
+    for dir in dirs {
+        match Brick::load(dir.clone()) {
+            Ok(brick) => bricks.push((dir, brick)),
+            Err(error) => failed.push((dir, error)),
+        }
+    }
+    let unresolved: Vec<Brick> = bricks.iter().map(|(_, brick)| brick.clone()).collect();
+    let mut resolved = Vec::new();
+    for (dir, brick) in bricks {
+        match brick.resolve_extends(&unresolved) {
+            Ok(brick) => resolved.push(brick),
+            Err(error) => failed.push((dir, error)),
+        }
+    }
+    (resolved, failed)
 }
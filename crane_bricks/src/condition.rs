@@ -0,0 +1,133 @@
+use std::{collections::HashMap, env, fmt, fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+/// A condition that decides if an action runs.
+///
+/// ## Example
+///
+/// ```toml
+/// [[actions]]
+/// action = "run_command"
+/// command = "cargo add serde"
+/// when = { all = [
+///     { file_exists = "Cargo.toml" },
+///     { command_exists = "cargo" },
+///     { not = { file_contains = { path = "Cargo.toml", text = "serde" } } },
+/// ] }
+/// ```
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Condition {
+    /// A file or directory exists, relative to the target dir
+    FileExists(String),
+    /// A file exists and contains the text
+    FileContains {
+        path: String,
+        text: String,
+    },
+    /// A variable is set, or has the given value
+    Var {
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        equals: Option<String>,
+    },
+    /// An environment variable is set, or has the given value
+    Env {
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        equals: Option<String>,
+    },
+    /// A command can be found in `PATH`
+    CommandExists(String),
+    All(Vec<Condition>),
+    Any(Vec<Condition>),
+    Not(Box<Condition>),
+}
+
+impl Condition {
+    pub fn evaluate(&self, cwd: &Path, variables: &HashMap<String, String>) -> bool {
+        match self {
+            Condition::FileExists(path) => cwd.join(path).exists(),
+            Condition::FileContains { path, text } => fs::read_to_string(cwd.join(path))
+                .map(|content| content.contains(text.as_str()))
+                .unwrap_or(false),
+            Condition::Var { name, equals } => {
+                matches_value(variables.get(name).map(String::as_str), equals)
+            }
+            Condition::Env { name, equals } => {
+                matches_value(env::var(name).ok().as_deref(), equals)
+            }
+            Condition::CommandExists(command) => command_exists(command),
+            Condition::All(conditions) => conditions
+                .iter()
+                .all(|condition| condition.evaluate(cwd, variables)),
+            Condition::Any(conditions) => conditions
+                .iter()
+                .any(|condition| condition.evaluate(cwd, variables)),
+            Condition::Not(condition) => !condition.evaluate(cwd, variables),
+        }
+    }
+}
+
+fn matches_value(value: Option<&str>, expected: &Option<String>) -> bool {
+    match (value, expected) {
+        (Some(value), Some(expected)) => value == expected,
+        (Some(_), None) => true,
+        (None, _) => false,
+    }
+}
+
+/// Check if an executable with the name exists in one of the `PATH` dirs
+fn command_exists(command: &str) -> bool {
+    let Some(paths) = env::var_os("PATH") else {
+        return false;
+    };
+    env::split_paths(&paths).any(|dir| {
+        let path = dir.join(command);
+        path.is_file() || (cfg!(windows) && path.with_extension("exe").is_file())
+    })
+}
+
+fn join(conditions: &[Condition], separator: &str) -> String {
+    conditions
+        .iter()
+        .map(|condition| match condition {
+            Condition::All(_) | Condition::Any(_) => format!("({})", condition),
+            _ => condition.to_string(),
+        })
+        .collect::<Vec<String>>()
+        .join(separator)
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Condition::FileExists(path) => write!(f, "file '{}' exists", path),
+            Condition::FileContains { path, text } => {
+                write!(f, "file '{}' contains '{}'", path, text)
+            }
+            Condition::Var { name, equals: None } => {
+                write!(f, "variable '{}' is set", name)
+            }
+            Condition::Var {
+                name,
+                equals: Some(value),
+            } => write!(f, "variable '{}' is '{}'", name, value),
+            Condition::Env { name, equals: None } => write!(f, "env '{}' is set", name),
+            Condition::Env {
+                name,
+                equals: Some(value),
+            } => write!(f, "env '{}' is '{}'", name, value),
+            Condition::CommandExists(command) => {
+                write!(f, "command '{}' exists", command)
+            }
+            Condition::All(conditions) => write!(f, "{}", join(conditions, " and ")),
+            Condition::Any(conditions) => write!(f, "{}", join(conditions, " or ")),
+            Condition::Not(condition) => match condition.as_ref() {
+                Condition::All(_) | Condition::Any(_) => write!(f, "not ({})", condition),
+                _ => write!(f, "not {}", condition),
+            },
+        }
+    }
+}
@@ -1,9 +1,171 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{
+    brick::Brick,
+    event::{Event, FileChange},
+    journal::Change,
+};
+
+type Listener = Rc<dyn Fn(&Event)>;
+
 pub struct ActionContext {
     pub dry_run: bool,
+
+    /// Variables set by the user, they take precedence over brick defaults
+    pub variables: HashMap<String, String>,
+
+    /// All bricks that can be included by other bricks
+    bricks: Rc<Vec<Brick>>,
+
+    /// Names of the bricks that are currently executing, innermost last
+    stack: Rc<RefCell<Vec<String>>>,
+
+    /// All changes made so far, used to roll back failed bricks
+    journal: Rc<RefCell<Vec<Change>>>,
+
+    /// Content files would have after a dry run, `None` if they are removed
+    planned: Rc<RefCell<BTreeMap<PathBuf, Option<Vec<u8>>>>>,
+
+    /// Called for everything that happens while executing bricks
+    listener: Option<Listener>,
 }
 
 impl ActionContext {
     pub fn new(dry_run: bool) -> Self {
-        Self { dry_run }
+        Self {
+            dry_run,
+            variables: HashMap::new(),
+            bricks: Rc::new(Vec::new()),
+            stack: Rc::new(RefCell::new(Vec::new())),
+            journal: Rc::new(RefCell::new(Vec::new())),
+            planned: Rc::new(RefCell::new(BTreeMap::new())),
+            listener: None,
+        }
+    }
+
+    pub fn with_variables(mut self, variables: HashMap<String, String>) -> Self {
+        self.variables = variables;
+        self
+    }
+
+    pub fn with_bricks(mut self, bricks: Vec<Brick>) -> Self {
+        self.bricks = Rc::new(bricks);
+        self
+    }
+
+    pub fn with_listener(mut self, listener: impl Fn(&Event) + 'static) -> Self {
+        self.listener = Some(Rc::new(listener));
+        self
+    }
+
+    /// Report an event to the listener, if there is one
+    pub fn emit(&self, event: Event) {
+        if let Some(listener) = &self.listener {
+            listener(&event);
+        }
+    }
+
+    /// A context sharing the journal of this one, with extra variables that
+    /// overwrite the existing ones
+    pub fn child(&self, variables: HashMap<String, String>) -> Self {
+        let mut child_variables = self.variables.clone();
+        child_variables.extend(variables);
+        Self {
+            dry_run: self.dry_run,
+            variables: child_variables,
+            bricks: Rc::clone(&self.bricks),
+            stack: Rc::clone(&self.stack),
+            journal: Rc::clone(&self.journal),
+            planned: Rc::clone(&self.planned),
+            listener: self.listener.clone(),
+        }
+    }
+
+    pub fn bricks(&self) -> &[Brick] {
+        &self.bricks
+    }
+
+    /// Mark a brick as executing, see [`Self::is_executing`]
+    pub fn enter(&self, brick: &str) {
+        self.stack.borrow_mut().push(brick.to_string());
+    }
+
+    pub fn leave(&self) {
+        self.stack.borrow_mut().pop();
+    }
+
+    /// Names of the bricks that are currently executing, outermost first
+    pub fn executing(&self) -> Vec<String> {
+        self.stack.borrow().clone()
+    }
+
+    pub fn is_executing(&self, brick: &str) -> bool {
+        self.stack
+            .borrow()
+            .iter()
+            .any(|name| name.to_lowercase() == brick.to_lowercase())
+    }
+
+    pub fn record(&self, change: Change) {
+        self.emit(Event::from(&change));
+        self.journal.borrow_mut().push(change);
+    }
+
+    /// Marks the current position in the journal, see [`Self::rollback_to`]
+    pub fn checkpoint(&self) -> usize {
+        self.journal.borrow().len()
+    }
+
+    /// Undo all changes made after the checkpoint, newest first
+    pub fn rollback_to(&self, checkpoint: usize) {
+        let changes: Vec<Change> =
+            self.journal.borrow_mut().drain(checkpoint..).collect();
+        if changes.is_empty() {
+            return;
+        }
+        info!("Rolling back {} change(s)", changes.len());
+        for change in changes.iter().rev() {
+            if let Err(error) = change.revert() {
+                warn!("Failed to roll back {:?}: {}", change, error);
+            }
+        }
+    }
+
+    pub fn changes(&self) -> Vec<Change> {
+        self.journal.borrow().clone()
+    }
+
+    /// Remember the content a file would have in a dry run, `None` removes it
+    pub fn plan(&self, path: &Path, content: Option<Vec<u8>>) {
+        let change = match content {
+            Some(_) => FileChange::PlannedWrite,
+            None => FileChange::PlannedRemoval,
+        };
+        self.emit(Event::FileChanged {
+            path: path.to_path_buf(),
+            change,
+        });
+        self.planned
+            .borrow_mut()
+            .insert(path.to_path_buf(), content);
+    }
+
+    /// The planned content of a file, `None` if the dry run didn't touch it
+    pub fn planned(&self, path: &Path) -> Option<Option<Vec<u8>>> {
+        self.planned.borrow().get(path).cloned()
+    }
+
+    /// All files the dry run would write or remove, sorted by path
+    pub fn planned_files(&self) -> Vec<(PathBuf, Option<Vec<u8>>)> {
+        self.planned
+            .borrow()
+            .iter()
+            .map(|(path, content)| (path.clone(), content.clone()))
+            .collect()
     }
 }
@@ -0,0 +1,114 @@
+use std::{
+    fmt,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+/// An error at a position in a file.
+///
+/// It displays as a single line like `brick.toml:4:1: Unknown key 'sourcs'`,
+/// [`Self::snippet`] has the line of the file to show below it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    path: PathBuf,
+    message: String,
+    /// Line and column the error starts at, counting from 1
+    position: Option<(usize, usize)>,
+    /// The line the error starts at, and the part of it that is marked
+    snippet: Option<(String, Range<usize>)>,
+}
+
+impl Diagnostic {
+    /// An error in the file at `path` with the content `source`, at the byte
+    /// range `span` of it
+    pub fn new(
+        path: &Path,
+        source: &str,
+        message: impl Into<String>,
+        span: Option<Range<usize>>,
+    ) -> Self {
+        let mut diagnostic = Self {
+            path: path.to_path_buf(),
+            message: message.into(),
+            position: None,
+            snippet: None,
+        };
+        let Some(span) = span.filter(|span| span.start <= source.len()) else {
+            return diagnostic;
+        };
+        let line_start = source[..span.start]
+            .rfind('\n')
+            .map_or(0, |index| index + 1);
+        let line_end = source[span.start..]
+            .find('\n')
+            .map_or(source.len(), |index| span.start + index);
+        let line = source[line_start..line_end].trim_end_matches('\r');
+        let column = source[line_start..span.start].chars().count();
+        // Spans over multiple lines are marked until the end of the first
+        let marked = source[span.start..span.end.clamp(span.start, line_end)]
+            .chars()
+            .count()
+            .max(1);
+        diagnostic.position =
+            Some((source[..line_start].lines().count() + 1, column + 1));
+        diagnostic.snippet = Some((line.to_string(), column..column + marked));
+        diagnostic
+    }
+
+    /// A parse error of a toml file
+    pub fn from_toml(path: &Path, source: &str, error: &toml::de::Error) -> Self {
+        Self::new(path, source, error.message().trim(), error.span())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Line the error is at, counting from 1
+    pub fn line(&self) -> Option<usize> {
+        self.position.map(|(line, _)| line)
+    }
+
+    /// Column the error starts at, counting from 1
+    pub fn column(&self) -> Option<usize> {
+        self.position.map(|(_, column)| column)
+    }
+
+    /// Where the error is, like `brick.toml:4:1`
+    pub fn location(&self) -> String {
+        match self.position {
+            Some((line, column)) => {
+                format!("{}:{}:{}", self.path.display(), line, column)
+            }
+            None => self.path.display().to_string(),
+        }
+    }
+
+    /// The line the error is at with the line number, and the line that marks
+    /// the error below it
+    pub fn snippet(&self) -> Option<(String, String)> {
+        let ((line, marked), number) = self.snippet.as_ref().zip(self.line())?;
+        let gutter = " ".repeat(number.to_string().len());
+        Some((
+            format!("{} | {}", number, line),
+            format!(
+                "{} | {}{}",
+                gutter,
+                " ".repeat(marked.start),
+                "^".repeat(marked.len())
+            ),
+        ))
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location(), self.message)
+    }
+}
+
+impl std::error::Error for Diagnostic {}
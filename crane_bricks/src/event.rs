@@ -0,0 +1,81 @@
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::journal::Change;
+
+/// Something that happened while executing bricks, see
+/// [`ActionContext::with_listener`](crate::context::ActionContext::with_listener)
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    BrickStarted {
+        brick: String,
+    },
+    BrickFinished {
+        brick: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+    /// The changes of a failed brick were undone
+    RolledBack {
+        brick: String,
+    },
+    ActionExecuted {
+        brick: String,
+        action: String,
+    },
+    ActionSkipped {
+        brick: String,
+        action: String,
+        reason: String,
+    },
+    FileChanged {
+        path: PathBuf,
+        change: FileChange,
+    },
+    CommandRun {
+        command: String,
+        dry_run: bool,
+    },
+    /// An error outside of a brick, e.g. a brick that could not be found
+    Error {
+        message: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FileChange {
+    Created,
+    CreatedDir,
+    Modified,
+    Removed,
+    /// Moved here from another path
+    Moved {
+        from: PathBuf,
+    },
+    ModeChanged,
+    /// Would be written by a dry run
+    PlannedWrite,
+    /// Would be removed by a dry run
+    PlannedRemoval,
+}
+
+impl From<&Change> for Event {
+    fn from(change: &Change) -> Self {
+        let (path, change) = match change {
+            Change::CreatedFile(path) => (path, FileChange::Created),
+            Change::CreatedDir(path) => (path, FileChange::CreatedDir),
+            Change::ModifiedFile { path, .. } => (path, FileChange::Modified),
+            Change::RemovedFile { path, .. } => (path, FileChange::Removed),
+            Change::RemovedSymlink { path, .. } => (path, FileChange::Removed),
+            Change::Moved { from, to } => (to, FileChange::Moved { from: from.clone() }),
+            Change::ModeChanged { path, .. } => (path, FileChange::ModeChanged),
+        };
+        Event::FileChanged {
+            path: path.clone(),
+            change,
+        }
+    }
+}
@@ -6,7 +6,11 @@ use std::{
 
 use anyhow::anyhow;
 
-use crate::context::ActionContext;
+use crate::{context::ActionContext, journal::Change, project::PROJECT_DIR};
+
+/// Directories that are never part of a brick or a snapshot, as they belong
+/// to version control, crane itself or build tools
+pub const SKIPPED_DIRS: [&str; 4] = [".git", PROJECT_DIR, "target", "node_modules"];
 
 pub fn sub_dirs(dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
     Ok(sub_paths(dir)?
@@ -27,60 +31,316 @@ pub fn sub_paths(dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
         .collect())
 }
 
+/// How many bytes are inspected when guessing if content is binary
+const BINARY_SNIFF_LEN: usize = 8000;
+
+/// Guess if the given content is binary.
+///
+/// Content is treated as binary if it contains a NUL byte near the start
+/// (the same heuristic git uses) or if it is not valid UTF-8.
+pub fn is_binary(content: &[u8]) -> bool {
+    let sniff = &content[..content.len().min(BINARY_SNIFF_LEN)];
+    sniff.contains(&0) || str::from_utf8(content).is_err()
+}
+
+/// Check if something exists at the path, without following symlinks.
+/// Unlike [`Path::exists`], this is also true for dangling symlinks.
+pub fn path_exists(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok()
+}
+
+/// Like [`path_exists`], but includes the changes planned by a dry run
+pub fn file_exists(ctx: &ActionContext, path: &Path) -> bool {
+    match ctx.planned(path) {
+        Some(planned) => planned.is_some(),
+        None => path_exists(path),
+    }
+}
+
 pub fn file_create_new(
     ctx: &ActionContext,
     path: &Path,
-    content: Option<String>,
+    content: impl AsRef<[u8]>,
 ) -> anyhow::Result<()> {
-    if !ctx.dry_run {
+    if ctx.dry_run {
+        ctx.plan(path, Some(content.as_ref().to_vec()));
+    } else {
         debug!("Creating new file '{:?}'", path);
         let mut file = File::create_new(path)?;
-        file.write_all(content.unwrap_or_default().as_bytes())?;
+        ctx.record(Change::CreatedFile(path.to_path_buf()));
+        file.write_all(content.as_ref())?;
     }
     Ok(())
 }
 
-pub fn file_read_content(ctx: &ActionContext, path: &Path) -> anyhow::Result<String> {
-    if ctx.dry_run && !path.exists() {
-        return Ok(String::new());
+/// Content of a file, including the changes planned by a dry run
+fn current_content(ctx: &ActionContext, path: &Path) -> Option<Vec<u8>> {
+    match ctx.planned(path) {
+        Some(planned) => planned,
+        None => fs::read(path).ok(),
     }
-    if !path.exists() {
-        return Err(anyhow::Error::new(io::Error::new(
-            io::ErrorKind::NotFound,
-            "Target file not found",
-        )));
+}
+
+pub fn file_read_content(ctx: &ActionContext, path: &Path) -> anyhow::Result<String> {
+    let content = if ctx.dry_run {
+        match current_content(ctx, path) {
+            Some(content) => content,
+            None => return Ok(String::new()),
+        }
+    } else {
+        if !path.exists() {
+            return Err(anyhow::Error::new(io::Error::new(
+                io::ErrorKind::NotFound,
+                "Target file not found",
+            )));
+        }
+        debug!("Reading content of file");
+        fs::read(path)?
+    };
+    if is_binary(&content) {
+        return Err(anyhow!(
+            "Target file '{}' is not a text file",
+            path.display()
+        ));
     }
-    debug!("Reading content of file");
-    Ok(fs::read_to_string(path).unwrap_or_default())
+    Ok(String::from_utf8(content)?)
 }
 
 pub fn file_replace_content(
     ctx: &ActionContext,
     path: &Path,
-    content: &String,
+    content: impl AsRef<[u8]>,
 ) -> anyhow::Result<()> {
     debug!("Replacing contents of '{:?}'", path.display());
     if ctx.dry_run {
+        ctx.plan(path, Some(content.as_ref().to_vec()));
         return Ok(());
     }
+    record_write(ctx, path)?;
     let mut file = File::options()
         .write(true)
         .create(true)
         .truncate(true)
         .open(path)?;
-    file.write_all(content.as_bytes())?;
+    file.write_all(content.as_ref())?;
+    Ok(())
+}
+
+/// Remember the current state of a file before it gets written to
+fn record_write(ctx: &ActionContext, path: &Path) -> anyhow::Result<()> {
+    if path.exists() {
+        ctx.record(Change::ModifiedFile {
+            path: path.to_path_buf(),
+            original: fs::read(path)?,
+        });
+    } else {
+        ctx.record(Change::CreatedFile(path.to_path_buf()));
+    }
     Ok(())
 }
 
 pub fn file_append_content(
     ctx: &ActionContext,
     path: &Path,
-    content: &String,
+    content: impl AsRef<[u8]>,
 ) -> anyhow::Result<()> {
     if ctx.dry_run {
+        let mut planned = current_content(ctx, path).unwrap_or_default();
+        planned.extend_from_slice(content.as_ref());
+        ctx.plan(path, Some(planned));
         return Ok(());
     }
+    record_write(ctx, path)?;
     let mut file = File::options().append(true).create(true).open(path)?;
-    file.write_all(content.as_bytes())?;
+    file.write_all(content.as_ref())?;
+    Ok(())
+}
+
+/// Parse an octal permission string like `"0755"` or `"644"`
+pub fn parse_mode(mode: &str) -> anyhow::Result<u32> {
+    let digits = mode.trim().trim_start_matches("0o");
+    let parsed = u32::from_str_radix(digits, 8).map_err(|_| {
+        anyhow!("Invalid file mode '{}', expected octal like '0755'", mode)
+    })?;
+    if parsed > 0o7777 {
+        return Err(anyhow!("Invalid file mode '{}', value is too large", mode));
+    }
+    Ok(parsed)
+}
+
+/// Get the permission bits of a file, without the setuid, setgid and sticky
+/// bits. Always `None` on non-unix platforms.
+pub fn file_mode(path: &Path) -> Option<u32> {
+    full_mode(path).map(|mode| mode & 0o777)
+}
+
+/// Get the permission bits of a file including the special bits, so a
+/// rollback restores them as they were
+fn full_mode(path: &Path) -> Option<u32> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::metadata(path)
+            .ok()
+            .map(|meta| meta.permissions().mode() & 0o7777)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        None
+    }
+}
+
+pub fn file_set_mode(ctx: &ActionContext, path: &Path, mode: u32) -> anyhow::Result<()> {
+    debug!("Setting mode of '{}' to {:o}", path.display(), mode);
+    if ctx.dry_run {
+        return Ok(());
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if let Some(original) = full_mode(path) {
+            ctx.record(Change::ModeChanged {
+                path: path.to_path_buf(),
+                original,
+            });
+        }
+        fs::set_permissions(path, fs::Permissions::from_mode(mode))?;
+    }
+    #[cfg(not(unix))]
+    {
+        warn!(
+            "File modes are not supported on this platform, skipping '{}'",
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+/// Create a symlink at `link` that points to `target`
+pub fn symlink_create(
+    ctx: &ActionContext,
+    target: &Path,
+    link: &Path,
+) -> anyhow::Result<()> {
+    debug!(
+        "Creating symlink '{}' -> '{}'",
+        link.display(),
+        target.display()
+    );
+    if ctx.dry_run {
+        return Ok(());
+    }
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(target, link)?;
+    #[cfg(windows)]
+    {
+        let resolved = link.parent().unwrap_or(Path::new(".")).join(target);
+        if resolved.is_dir() {
+            std::os::windows::fs::symlink_dir(target, link)?;
+        } else {
+            std::os::windows::fs::symlink_file(target, link)?;
+        }
+    }
+    ctx.record(Change::CreatedFile(link.to_path_buf()));
+    Ok(())
+}
+
+/// Remove a file or symlink
+pub fn file_remove(ctx: &ActionContext, path: &Path) -> anyhow::Result<()> {
+    debug!("Removing '{}'", path.display());
+    if ctx.dry_run {
+        ctx.plan(path, None);
+        return Ok(());
+    }
+    let metadata = fs::symlink_metadata(path)?;
+    if metadata.is_dir() {
+        return Err(anyhow!("'{}' is a directory", path.display()));
+    }
+    let change = if metadata.is_symlink() {
+        Change::RemovedSymlink {
+            path: path.to_path_buf(),
+            target: fs::read_link(path)?,
+        }
+    } else {
+        Change::RemovedFile {
+            path: path.to_path_buf(),
+            original: fs::read(path)?,
+            mode: full_mode(path),
+        }
+    };
+    fs::remove_file(path)?;
+    ctx.record(change);
     Ok(())
 }
+
+/// Create a directory and all missing parents
+pub fn dir_create_all(ctx: &ActionContext, path: &Path) -> anyhow::Result<()> {
+    if path.is_dir() {
+        return Ok(());
+    }
+    debug!("Creating directory '{}'", path.display());
+    if ctx.dry_run {
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        dir_create_all(ctx, parent)?;
+    }
+    fs::create_dir(path)?;
+    ctx.record(Change::CreatedDir(path.to_path_buf()));
+    Ok(())
+}
+
+/// Move a file, creating missing parent directories of the destination
+pub fn file_move(ctx: &ActionContext, from: &Path, to: &Path) -> anyhow::Result<()> {
+    debug!("Moving '{}' to '{}'", from.display(), to.display());
+    if ctx.dry_run {
+        ctx.plan(to, current_content(ctx, from));
+        ctx.plan(from, None);
+        return Ok(());
+    }
+    if let Some(parent) = to.parent() {
+        dir_create_all(ctx, parent)?;
+    }
+    fs::rename(from, to)?;
+    ctx.record(Change::Moved {
+        from: from.to_path_buf(),
+        to: to.to_path_buf(),
+    });
+    Ok(())
+}
+
+/// Copy a file, creating missing parent directories of the destination
+pub fn file_copy(ctx: &ActionContext, from: &Path, to: &Path) -> anyhow::Result<()> {
+    debug!("Copying '{}' to '{}'", from.display(), to.display());
+    if ctx.dry_run {
+        ctx.plan(to, current_content(ctx, from));
+        return Ok(());
+    }
+    if let Some(parent) = to.parent() {
+        dir_create_all(ctx, parent)?;
+    }
+    record_write(ctx, to)?;
+    fs::copy(from, to)?;
+    Ok(())
+}
+
+/// Get all paths in `dir` that match the glob `pattern`.
+///
+/// A pattern without glob characters matches the path if it exists.
+pub fn glob_paths(dir: &Path, pattern: &str) -> anyhow::Result<Vec<PathBuf>> {
+    let full_pattern = format!(
+        "{}/{}",
+        glob::Pattern::escape(&dir.display().to_string()),
+        pattern
+    );
+    let mut paths = Vec::new();
+    for entry in glob::glob(&full_pattern)? {
+        paths.push(entry?);
+    }
+    if paths.is_empty() && path_exists(&dir.join(pattern)) {
+        // Dangling symlinks are not reported by glob
+        paths.push(dir.join(pattern));
+    }
+    Ok(paths)
+}
@@ -0,0 +1,73 @@
+use std::{fs, path::PathBuf};
+
+/// A change that was made to the file system while executing actions.
+///
+/// Every change keeps enough information to undo it again.
+#[derive(Debug, Clone)]
+pub enum Change {
+    CreatedFile(PathBuf),
+    CreatedDir(PathBuf),
+    ModifiedFile {
+        path: PathBuf,
+        original: Vec<u8>,
+    },
+    RemovedFile {
+        path: PathBuf,
+        original: Vec<u8>,
+        mode: Option<u32>,
+    },
+    RemovedSymlink {
+        path: PathBuf,
+        target: PathBuf,
+    },
+    Moved {
+        from: PathBuf,
+        to: PathBuf,
+    },
+    ModeChanged {
+        path: PathBuf,
+        original: u32,
+    },
+}
+
+impl Change {
+    /// Undo the change
+    pub fn revert(&self) -> anyhow::Result<()> {
+        debug!("Reverting {:?}", self);
+        match self {
+            Change::CreatedFile(path) => fs::remove_file(path)?,
+            Change::CreatedDir(path) => fs::remove_dir(path)?,
+            Change::ModifiedFile { path, original } => fs::write(path, original)?,
+            Change::RemovedFile {
+                path,
+                original,
+                mode,
+            } => {
+                fs::write(path, original)?;
+                if let Some(mode) = mode {
+                    set_mode(path, *mode)?;
+                }
+            }
+            Change::RemovedSymlink { path, target } => {
+                #[cfg(unix)]
+                std::os::unix::fs::symlink(target, path)?;
+                #[cfg(windows)]
+                std::os::windows::fs::symlink_file(target, path)?;
+            }
+            Change::Moved { from, to } => fs::rename(to, from)?,
+            Change::ModeChanged { path, original } => set_mode(path, *original)?,
+        }
+        Ok(())
+    }
+}
+
+fn set_mode(path: &std::path::Path, mode: u32) -> anyhow::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(mode))?;
+    }
+    #[cfg(not(unix))]
+    let _ = (path, mode);
+    Ok(())
+}
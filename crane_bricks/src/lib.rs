@@ -3,5 +3,13 @@ extern crate log;
 
 pub mod actions;
 pub mod brick;
+pub mod condition;
 pub mod context;
+pub mod diagnostic;
+pub mod event;
 pub mod file_utils;
+pub mod journal;
+pub mod project;
+pub mod resolve;
+pub mod template;
+pub mod validate;
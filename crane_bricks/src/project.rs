@@ -0,0 +1,85 @@
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{brick::Brick, context::ActionContext, file_utils::dir_create_all};
+
+/// Directory inside a project where crane keeps its state
+pub const PROJECT_DIR: &str = ".crane";
+
+const APPLIED_FILE: &str = "applied.toml";
+
+/// A brick that was applied to a project
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct AppliedBrick {
+    name: String,
+
+    #[serde(default)]
+    conflicts: Vec<String>,
+
+    #[serde(default)]
+    provides: Vec<String>,
+}
+
+impl AppliedBrick {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn conflicts(&self) -> &[String] {
+        &self.conflicts
+    }
+
+    pub fn provides(&self) -> &[String] {
+        &self.provides
+    }
+}
+
+impl From<&Brick> for AppliedBrick {
+    fn from(brick: &Brick) -> Self {
+        Self {
+            name: brick.name().to_string(),
+            conflicts: brick.config().conflicts().to_vec(),
+            provides: brick.config().provides().to_vec(),
+        }
+    }
+}
+
+/// The bricks that were applied to a project, stored in `.crane/applied.toml`
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProjectRecord {
+    #[serde(default)]
+    applied: Vec<AppliedBrick>,
+}
+
+impl ProjectRecord {
+    /// Load the record of a project, empty if nothing was applied yet
+    pub fn load(project_dir: &Path) -> anyhow::Result<Self> {
+        let path = project_dir.join(PROJECT_DIR).join(APPLIED_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(toml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, ctx: &ActionContext, project_dir: &Path) -> anyhow::Result<()> {
+        if ctx.dry_run {
+            return Ok(());
+        }
+        let dir = project_dir.join(PROJECT_DIR);
+        dir_create_all(ctx, &dir)?;
+        fs::write(dir.join(APPLIED_FILE), toml::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn applied(&self) -> &[AppliedBrick] {
+        &self.applied
+    }
+
+    /// Remember a brick as applied, replacing an older entry of it
+    pub fn add(&mut self, brick: &Brick) {
+        self.applied
+            .retain(|applied| applied.name.to_lowercase() != brick.name().to_lowercase());
+        self.applied.push(AppliedBrick::from(brick));
+    }
+}
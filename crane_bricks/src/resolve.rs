@@ -0,0 +1,153 @@
+use anyhow::anyhow;
+
+use crate::{brick::Brick, project::AppliedBrick};
+
+/// Find a brick by its name, ignoring case
+pub fn find_brick<'a>(bricks: &'a [Brick], name: &str) -> Option<&'a Brick> {
+    bricks
+        .iter()
+        .find(|brick| brick.name().to_lowercase() == name.to_lowercase())
+}
+
+/// Order the requested bricks and all bricks they require, so that every
+/// brick comes after its requirements. Each brick is included only once.
+///
+/// Fails before anything is executed if a requirement is missing or the
+/// requirements form a cycle.
+pub fn resolve_requirements<'a>(
+    requested: &[&'a Brick],
+    available: &'a [Brick],
+) -> anyhow::Result<Vec<&'a Brick>> {
+    let mut resolver = Resolver {
+        available,
+        ordered: Vec::new(),
+        stack: Vec::new(),
+        errors: Vec::new(),
+    };
+    for brick in requested {
+        resolver.visit(brick);
+    }
+    if !resolver.errors.is_empty() {
+        return Err(anyhow!(resolver.errors.join("\n")));
+    }
+    Ok(resolver.ordered)
+}
+
+struct Resolver<'a> {
+    available: &'a [Brick],
+    ordered: Vec<&'a Brick>,
+    /// Bricks that are currently being resolved, used to detect cycles
+    stack: Vec<&'a Brick>,
+    errors: Vec<String>,
+}
+
+impl<'a> Resolver<'a> {
+    fn visit(&mut self, brick: &'a Brick) {
+        if self.ordered.iter().any(|other| same_brick(other, brick)) {
+            return;
+        }
+        if let Some(start) = self.stack.iter().position(|other| same_brick(other, brick))
+        {
+            let mut cycle: Vec<&str> = self.stack[start..]
+                .iter()
+                .map(|brick| brick.name())
+                .collect();
+            cycle.push(brick.name());
+            self.errors.push(format!(
+                "Brick requirements form a cycle: {}",
+                cycle.join(" → ")
+            ));
+            return;
+        }
+        self.stack.push(brick);
+        for requirement in brick.config().requires() {
+            match find_brick(self.available, requirement) {
+                Some(required) => self.visit(required),
+                None => self.errors.push(format!(
+                    "Brick '{}' requires '{}', which could not be found",
+                    brick.name(),
+                    requirement
+                )),
+            }
+        }
+        self.stack.pop();
+        self.ordered.push(brick);
+    }
+}
+
+fn same_brick(a: &Brick, b: &Brick) -> bool {
+    a.name().to_lowercase() == b.name().to_lowercase()
+}
+
+/// Check that none of the bricks conflict with each other or with the bricks
+/// that were already applied to the project.
+pub fn check_conflicts(
+    bricks: &[&Brick],
+    applied: &[AppliedBrick],
+) -> anyhow::Result<()> {
+    let new: Vec<AppliedBrick> = bricks
+        .iter()
+        .map(|brick| AppliedBrick::from(*brick))
+        .collect();
+    let mut errors = Vec::new();
+    for (index, brick) in new.iter().enumerate() {
+        for other in &new[index + 1..] {
+            if let Some(reason) = conflict_reason(brick, other) {
+                errors.push(format!(
+                    "'{}' conflicts with '{}' ({})",
+                    brick.name(),
+                    other.name(),
+                    reason
+                ));
+            }
+        }
+        for other in applied {
+            if brick.name().to_lowercase() == other.name().to_lowercase() {
+                continue;
+            }
+            if let Some(reason) = conflict_reason(brick, other) {
+                errors.push(format!(
+                    "'{}' conflicts with '{}', which is already applied to this project ({})",
+                    brick.name(),
+                    other.name(),
+                    reason
+                ));
+            }
+        }
+    }
+    if !errors.is_empty() {
+        return Err(anyhow!(errors.join("\n")));
+    }
+    Ok(())
+}
+
+fn conflict_reason(a: &AppliedBrick, b: &AppliedBrick) -> Option<String> {
+    for (brick, other) in [(a, b), (b, a)] {
+        for conflict in brick.conflicts() {
+            let conflict = conflict.to_lowercase();
+            if other.name().to_lowercase() == conflict {
+                return Some(format!("'{}' declares the conflict", brick.name()));
+            }
+            if other
+                .provides()
+                .iter()
+                .any(|capability| capability.to_lowercase() == conflict)
+            {
+                return Some(format!(
+                    "'{}' conflicts with '{}', which '{}' provides",
+                    brick.name(),
+                    conflict,
+                    other.name()
+                ));
+            }
+        }
+    }
+    a.provides()
+        .iter()
+        .find(|capability| {
+            b.provides()
+                .iter()
+                .any(|other| other.to_lowercase() == capability.to_lowercase())
+        })
+        .map(|capability| format!("both provide '{}'", capability))
+}
@@ -0,0 +1,43 @@
+use std::collections::HashMap;
+
+/// Replace `{{ name }}` placeholders with the value of the variable.
+///
+/// Placeholders of unknown variables are left untouched, so files that use
+/// the same syntax for something else (e.g. GitHub workflows) keep working.
+pub fn render(text: &str, variables: &HashMap<String, String>) -> String {
+    if variables.is_empty() {
+        return text.to_string();
+    }
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(length) = rest[start..].find("}}") else {
+            break;
+        };
+        let name = rest[start + 2..start + length].trim();
+        output.push_str(&rest[..start]);
+        match variables.get(name) {
+            Some(value) => output.push_str(value),
+            None => output.push_str(&rest[start..start + length + 2]),
+        }
+        rest = &rest[start + length + 2..];
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Names of all `{{ name }}` placeholders in the text with the byte offset
+/// they start at
+pub fn placeholders(text: &str) -> Vec<(usize, &str)> {
+    let mut placeholders = Vec::new();
+    let mut offset = 0;
+    while let Some(start) = text[offset..].find("{{") {
+        let start = offset + start;
+        let Some(length) = text[start..].find("}}") else {
+            break;
+        };
+        placeholders.push((start, text[start + 2..start + length].trim()));
+        offset = start + length + 2;
+    }
+    placeholders
+}
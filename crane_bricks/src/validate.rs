@@ -0,0 +1,816 @@
+use std::{fmt, fs, ops::Range, path::Path};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use toml::{
+    Spanned,
+    de::{DeTable, DeValue, ValueDeserializer},
+};
+
+use crate::{
+    actions::{Action, common::source_matches, patch_file::parse_hunks},
+    brick::{BRICK_CONFIG_FILE, Brick, BrickConfig},
+    condition::Condition,
+    diagnostic::Diagnostic,
+    file_utils::parse_mode,
+    template::placeholders,
+};
+
+const BRICK_KEYS: &[&str] = &[
+    "name",
+    "description",
+    "tags",
+    "authors",
+    "version",
+    "homepage",
+    "min_crane_version",
+    "extends",
+    "extend_actions",
+    "requires",
+    "conflicts",
+    "provides",
+    "variables",
+    "actions",
+    "variants",
+    "default_variant",
+    "features",
+];
+
+const VARIABLE_KEYS: &[&str] = &["name", "default", "description"];
+
+const PART_KEYS: &[&str] = &["name", "description", "files", "actions"];
+
+/// Keys every action has
+const COMMON_KEYS: &[&str] = &["action", "working_dir", "sources", "when"];
+
+/// The actions and their own keys
+const ACTION_KEYS: &[(&str, &[&str])] = &[
+    (
+        "insert_file",
+        &[
+            "if_file_exists",
+            "file_type",
+            "mode",
+            "destination",
+            "dotfiles",
+        ],
+    ),
+    (
+        "modify_file",
+        &["type", "content", "selector", "only_sources"],
+    ),
+    ("run_command", &["command"]),
+    ("chmod", &["mode"]),
+    ("symlink", &["target", "link", "if_file_exists"]),
+    ("delete_file", &[]),
+    ("move_file", &["from", "to", "if_file_exists"]),
+    ("copy_file", &["from", "to", "if_file_exists"]),
+    ("create_dir", &["path"]),
+    (
+        "include_brick",
+        &["brick", "variant", "features", "variables"],
+    ),
+    ("patch_file", &["path", "patch"]),
+];
+
+/// The conditions and their keys, if they take a table
+const CONDITION_KEYS: &[(&str, &[&str])] = &[
+    ("file_exists", &[]),
+    ("file_contains", &["path", "text"]),
+    ("var", &["name", "equals"]),
+    ("env", &["name", "equals"]),
+    ("command_exists", &[]),
+    ("all", &[]),
+    ("any", &[]),
+    ("not", &[]),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// The brick doesn't work as intended
+    Error,
+    /// The brick works, but probably not as intended
+    Warning,
+}
+
+/// A mistake found in a brick
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Problem {
+    pub severity: Severity,
+    /// Path of the key in `brick.toml` like `actions[1].sources`, or the name
+    /// of the brick file the problem is in
+    pub location: String,
+    pub message: String,
+    /// Byte range in `brick.toml`, if the problem is in it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub span: Option<Range<usize>>,
+    /// Line of the span in `brick.toml`, counting from 1
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    /// Column of the span in `brick.toml`, counting from 1
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+}
+
+impl Problem {
+    fn error(location: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            location: location.into(),
+            message: message.into(),
+            span: None,
+            line: None,
+            column: None,
+        }
+    }
+
+    fn warning(location: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            ..Self::error(location, message)
+        }
+    }
+
+    fn with_span(mut self, span: Range<usize>) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    /// The problem with the line of `brick.toml` it is at, `source` is the
+    /// content of the file at `path`
+    pub fn diagnostic(&self, path: &Path, source: &str) -> Diagnostic {
+        Diagnostic::new(path, source, self.to_string(), self.span.clone())
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.location.is_empty() {
+            return write!(f, "{}", self.message);
+        }
+        write!(f, "{}: {}", self.location, self.message)
+    }
+}
+
+/// Check the brick at `dir` for mistakes that are silently ignored or only
+/// show up when the brick is added, like unknown keys or missing files.
+///
+/// Returns no problems if the brick is fine.
+pub fn validate_brick(dir: &Path) -> Vec<Problem> {
+    let config_file = dir.join(BRICK_CONFIG_FILE);
+    if !config_file.exists() {
+        let mut problems = Vec::new();
+        match Brick::try_from(dir.to_path_buf()) {
+            Ok(brick) => {
+                let actions: Vec<(String, Action)> = brick
+                    .config()
+                    .actions()
+                    .iter()
+                    .enumerate()
+                    .map(|(index, action)| {
+                        (format!("actions[{}]", index), action.clone())
+                    })
+                    .collect();
+                check_brick(&brick, brick.config(), &actions, &mut problems)
+            }
+            Err(error) => problems.push(Problem::error("", error.to_string())),
+        }
+        return problems;
+    }
+    let text = match fs::read_to_string(&config_file) {
+        Ok(text) => text,
+        Err(error) => {
+            return vec![Problem::error(
+                BRICK_CONFIG_FILE,
+                format!("Could not read the file: {}", error),
+            )];
+        }
+    };
+    let mut problems = config_problems(dir, &text);
+    for problem in &mut problems {
+        let diagnostic = problem.diagnostic(&config_file, &text);
+        problem.line = diagnostic.line();
+        problem.column = diagnostic.column();
+    }
+    // Problems in brick.toml first, in the order they appear
+    problems.sort_by_key(|problem| {
+        problem.span.as_ref().map_or((1, 0), |span| (0, span.start))
+    });
+    problems
+}
+
+/// Problems of a brick with a config, `text` is the content of its config
+fn config_problems(dir: &Path, text: &str) -> Vec<Problem> {
+    let table = match DeTable::parse(text) {
+        Ok(table) => table,
+        Err(error) => {
+            let problem = Problem::error("", error.message().trim());
+            return vec![match error.span() {
+                Some(span) => problem.with_span(span),
+                None => problem,
+            }];
+        }
+    };
+    let mut problems = Vec::new();
+    check_keys(table.get_ref(), BRICK_KEYS, "", &mut problems);
+    // Actions that can't be parsed are reported on their own and blanked out,
+    // so the rest of the brick is still checked. Unknown actions and
+    // conditions are already reported with a suggestion.
+    let parse_error = toml::from_str::<BrickConfig>(text).err();
+    let mut checked = text.to_string();
+    let mut actions = Vec::new();
+    for (location, item, parsed) in parse_actions(table.get_ref()) {
+        match parsed {
+            Ok(action) => actions.push((location, action)),
+            Err(error) => {
+                let nested = format!("{}.", location);
+                if !problems.iter().any(|problem| {
+                    problem.location == location || problem.location.starts_with(&nested)
+                }) {
+                    problems.push(action_problem(
+                        &location,
+                        item,
+                        &error,
+                        parse_error.as_ref(),
+                    ));
+                }
+                blank(&mut checked, extent(item));
+            }
+        }
+    }
+    loop {
+        let error = match toml::from_str::<BrickConfig>(&checked) {
+            Ok(config) => {
+                let brick = Brick::new_with_config(config.clone(), dir.to_path_buf())
+                    .resolve_siblings();
+                match brick {
+                    Ok(brick) => check_brick(&brick, &config, &actions, &mut problems),
+                    Err(error) => {
+                        problems.push(Problem::error("extends", error.to_string()))
+                    }
+                }
+                break;
+            }
+            Err(error) => error,
+        };
+        let reported = error.span().and_then(|span| {
+            problems
+                .iter()
+                .find_map(|problem| reported_at(table.get_ref(), problem, &span))
+        });
+        match reported {
+            // A blanked part can't cause the error again
+            Some(range) if !checked[range.clone()].trim().is_empty() => {
+                blank(&mut checked, range)
+            }
+            _ => {
+                let problem = Problem::error("", error.message().trim());
+                problems.push(match error.span() {
+                    Some(span) => problem.with_span(span),
+                    None => problem,
+                });
+                break;
+            }
+        }
+    }
+    for problem in problems.iter_mut().filter(|problem| problem.span.is_none()) {
+        problem.span = lookup(table.get_ref(), &problem.location);
+    }
+    problems
+}
+
+/// The parse error of a brick config.
+///
+/// Parsing the whole config spans errors like a missing field to the first
+/// `[[actions]]` header, so an action that can't be parsed on its own is
+/// reported instead.
+pub(crate) fn config_error(
+    path: &Path,
+    text: &str,
+    error: &toml::de::Error,
+) -> Diagnostic {
+    let Ok(table) = DeTable::parse(text) else {
+        return Diagnostic::from_toml(path, text, error);
+    };
+    parse_actions(table.get_ref())
+        .into_iter()
+        .find_map(|(location, item, parsed)| {
+            let problem = action_problem(&location, item, &parsed.err()?, Some(error));
+            Some(problem.diagnostic(path, text))
+        })
+        .unwrap_or_else(|| Diagnostic::from_toml(path, text, error))
+}
+
+/// Every action of the config, including the ones of variants and features,
+/// parsed on its own. With its location like `variants[0].actions[1]`.
+fn parse_actions<'a>(
+    table: &'a DeTable,
+) -> Vec<(
+    String,
+    &'a Spanned<DeValue<'a>>,
+    Result<Action, toml::de::Error>,
+)> {
+    let mut lists = vec![String::from("actions")];
+    for kind in ["variants", "features"] {
+        let parts = find(table, kind).and_then(|parts| parts.get_ref().as_array());
+        let count = parts.map_or(0, |parts| parts.len());
+        lists.extend((0..count).map(|index| format!("{}[{}].actions", kind, index)));
+    }
+    let mut actions = Vec::new();
+    for list in lists {
+        let items = find(table, &list).and_then(|items| items.get_ref().as_array());
+        for (index, item) in items.into_iter().flatten().enumerate() {
+            let parsed = Action::deserialize(ValueDeserializer::from(item.clone()));
+            actions.push((format!("{}[{}]", list, index), item, parsed));
+        }
+    }
+    actions
+}
+
+/// The problem of an action that can't be parsed, at the action. The error of
+/// parsing the whole config is more precise if it is in the action.
+fn action_problem(
+    location: &str,
+    item: &Spanned<DeValue>,
+    error: &toml::de::Error,
+    parse_error: Option<&toml::de::Error>,
+) -> Problem {
+    let in_action = parse_error.and_then(|parse_error| {
+        let span = parse_error.span()?;
+        extent(item)
+            .contains(&span.start)
+            .then(|| (parse_error.message(), span))
+    });
+    match in_action {
+        Some((message, span)) => Problem::error(location, message.trim()).with_span(span),
+        None => Problem::error(location, error.message().trim()).with_span(item.span()),
+    }
+}
+
+/// Report keys of `table` that are not in `keys` and check the nested tables
+fn check_keys(
+    table: &DeTable,
+    keys: &[&str],
+    location: &str,
+    problems: &mut Vec<Problem>,
+) {
+    for (key, value) in table.iter() {
+        let name = key.get_ref().as_ref();
+        let location = if location.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", location, name)
+        };
+        if !keys.contains(&name) {
+            problems.push(unknown(name, "key", keys, &location).with_span(key.span()));
+            continue;
+        }
+        match (keys, name) {
+            (BRICK_KEYS, "variables") => {
+                for_each_table(value, &location, |table, location| {
+                    check_keys(table, VARIABLE_KEYS, location, problems)
+                });
+            }
+            (BRICK_KEYS | PART_KEYS, "actions") => {
+                for_each_table(value, &location, |table, location| {
+                    check_action_keys(table, location, problems)
+                });
+            }
+            (BRICK_KEYS, "variants" | "features") => {
+                for_each_table(value, &location, |table, location| {
+                    check_keys(table, PART_KEYS, location, problems)
+                });
+            }
+            (_, "when") => check_condition_keys(value, &location, problems),
+            _ => {}
+        }
+    }
+}
+
+fn check_action_keys(table: &DeTable, location: &str, problems: &mut Vec<Problem>) {
+    let Some((key, value)) = table
+        .iter()
+        .find(|(key, _)| key.get_ref().as_ref() == "action")
+    else {
+        // Missing actions are reported when parsing the config
+        return;
+    };
+    let Some(name) = value.get_ref().as_str() else {
+        return;
+    };
+    let Some((_, own_keys)) = ACTION_KEYS.iter().find(|(action, _)| *action == name)
+    else {
+        let names: Vec<&str> = ACTION_KEYS.iter().map(|(action, _)| *action).collect();
+        let location = format!("{}.{}", location, key.get_ref());
+        problems.push(unknown(name, "action", &names, &location).with_span(value.span()));
+        return;
+    };
+    let keys: Vec<&str> = COMMON_KEYS.iter().chain(*own_keys).copied().collect();
+    check_keys(table, &keys, location, problems);
+}
+
+fn check_condition_keys(
+    value: &Spanned<DeValue>,
+    location: &str,
+    problems: &mut Vec<Problem>,
+) {
+    let Some(table) = value.get_ref().as_table() else {
+        return;
+    };
+    let names: Vec<&str> = CONDITION_KEYS.iter().map(|(name, _)| *name).collect();
+    for (key, value) in table.iter() {
+        let name = key.get_ref().as_ref();
+        let location = format!("{}.{}", location, name);
+        let Some((_, keys)) = CONDITION_KEYS.iter().find(|(known, _)| *known == name)
+        else {
+            problems.push(
+                unknown(name, "condition", &names, &location).with_span(key.span()),
+            );
+            continue;
+        };
+        match name {
+            "all" | "any" => {
+                let conditions = value.get_ref().as_array().into_iter().flatten();
+                for (index, condition) in conditions.enumerate() {
+                    let location = format!("{}[{}]", location, index);
+                    check_condition_keys(condition, &location, problems);
+                }
+            }
+            "not" => check_condition_keys(value, &location, problems),
+            _ => {
+                if let Some(table) = value.get_ref().as_table() {
+                    check_keys(table, keys, &location, problems);
+                }
+            }
+        }
+    }
+}
+
+/// Call `check` for every table in an array of tables
+fn for_each_table(
+    value: &Spanned<DeValue>,
+    location: &str,
+    mut check: impl FnMut(&DeTable, &str),
+) {
+    let Some(array) = value.get_ref().as_array() else {
+        return;
+    };
+    for (index, item) in array.iter().enumerate() {
+        if let Some(table) = item.get_ref().as_table() {
+            check(table, &format!("{}[{}]", location, index));
+        }
+    }
+}
+
+fn unknown(name: &str, kind: &str, known: &[&str], location: &str) -> Problem {
+    match suggestion(name, known.iter().copied()) {
+        Some(suggestion) => Problem::error(
+            location,
+            format!(
+                "Unknown {} '{}', did you mean '{}'?",
+                kind, name, suggestion
+            ),
+        ),
+        None => Problem::error(location, format!("Unknown {} '{}'", kind, name)),
+    }
+}
+
+/// The name closest to `name`, if it's close enough to be a typo
+fn suggestion<'a>(
+    name: &str,
+    names: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    let max_distance = (name.chars().count() / 3).max(1);
+    names
+        .into_iter()
+        .map(|candidate| (candidate, strsim::damerau_levenshtein(name, candidate)))
+        .filter(|(_, distance)| *distance <= max_distance)
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(candidate, _)| candidate)
+}
+
+/// Span of the value at `location`, like `actions[1].sources`
+fn lookup(table: &DeTable, location: &str) -> Option<Range<usize>> {
+    find(table, location).map(Spanned::span)
+}
+
+/// The value at `location`, like `actions[1].sources`
+fn find<'a>(table: &'a DeTable, location: &str) -> Option<&'a Spanned<DeValue<'a>>> {
+    let mut found = None;
+    let mut current: Option<&DeValue> = None;
+    for segment in location.split('.') {
+        let mut indices = segment.split('[');
+        let key = indices.next()?;
+        let table = match current {
+            None => table,
+            Some(value) => value.as_table()?,
+        };
+        let (_, value) = table.iter().find(|(name, _)| name.get_ref() == key)?;
+        let mut value = value;
+        for index in indices {
+            let index: usize = index.strip_suffix(']')?.parse().ok()?;
+            value = value.get_ref().as_array()?.get(index)?;
+        }
+        found = Some(value);
+        current = Some(value.get_ref());
+    }
+    found
+}
+
+/// If `problem` is at `span` or in the table or value that starts there, the
+/// part of the file that contains it: the innermost array item it is in
+fn reported_at(
+    table: &DeTable,
+    problem: &Problem,
+    span: &Range<usize>,
+) -> Option<Range<usize>> {
+    let location = &problem.location;
+    let matches = problem.span.as_ref() == Some(span)
+        || location
+            .match_indices('.')
+            .filter_map(|(index, _)| lookup(table, &location[..index]))
+            .any(|parent| parent.start == span.start);
+    if !matches {
+        return None;
+    }
+    let item = location
+        .rfind(']')
+        .map_or(location.as_str(), |index| &location[..=index]);
+    find(table, item).map(extent)
+}
+
+/// The part of the file a value spans, including its nested values and the
+/// header of an array of tables
+fn extent(value: &Spanned<DeValue>) -> Range<usize> {
+    let mut range = value.span();
+    let nested: Vec<Range<usize>> = match value.get_ref() {
+        DeValue::Table(table) => table
+            .iter()
+            .flat_map(|(key, value)| [key.span(), extent(value)])
+            .collect(),
+        DeValue::Array(array) => array.iter().map(extent).collect(),
+        _ => Vec::new(),
+    };
+    for span in nested {
+        range.start = range.start.min(span.start);
+        range.end = range.end.max(span.end);
+    }
+    range
+}
+
+/// Replace a part of `text` with spaces, including a comma after it, so the
+/// rest keeps its position
+fn blank(text: &mut String, range: Range<usize>) {
+    let rest = &text[range.end..];
+    let end = match rest.trim_start().strip_prefix(',') {
+        Some(after) => text.len() - after.len(),
+        None => range.end,
+    };
+    // Multi-byte characters become multiple spaces to keep the byte positions
+    let blanked: String = text[range.start..end]
+        .chars()
+        .map(|char| match char {
+            '\n' => String::from("\n"),
+            _ => " ".repeat(char.len_utf8()),
+        })
+        .collect();
+    text.replace_range(range.start..end, &blanked);
+}
+
+/// What an action is checked against
+struct Checked<'a> {
+    files: Vec<String>,
+    variables: Vec<&'a str>,
+}
+
+/// Check the files, actions and templates of a brick.
+///
+/// `actions` are the brick's own actions with their location in
+/// `brick.toml`, files and variables are taken from the brick with its bases.
+fn check_brick(
+    brick: &Brick,
+    config: &BrickConfig,
+    actions: &[(String, Action)],
+    problems: &mut Vec<Problem>,
+) {
+    let files = brick.all_files();
+    let checked = Checked {
+        files: files.iter().map(|file| file.name().to_string()).collect(),
+        variables: brick
+            .config()
+            .variables()
+            .iter()
+            .map(|variable| variable.name())
+            .collect(),
+    };
+    if let Some(variant) = config.default_variant()
+        && config.variant(variant).is_none()
+    {
+        problems.push(Problem::error(
+            "default_variant",
+            format!("There is no variant '{}'", variant),
+        ));
+    }
+    for (location, action) in actions {
+        check_action(action, location, &checked, problems);
+    }
+    for (kind, parts) in [
+        ("variants", config.variants()),
+        ("features", config.features()),
+    ] {
+        for (index, part) in parts.iter().enumerate() {
+            let location = format!("{}[{}].files", kind, index);
+            for file in part.files() {
+                check_file(file, &location, &checked, problems);
+            }
+        }
+    }
+    for file in &files {
+        if let Some(text) = file.text()
+            && !file.is_symlink()
+        {
+            check_template(text, file.name(), &checked, problems);
+        }
+    }
+}
+
+fn check_action(
+    action: &Action,
+    location: &str,
+    checked: &Checked,
+    problems: &mut Vec<Problem>,
+) {
+    let sources = format!("{}.sources", location);
+    for source in &action.common().sources {
+        if let Some(pattern) = source.strip_prefix("re:") {
+            match Regex::new(pattern) {
+                Ok(_) if !matches!(action, Action::InsertFile(_)) => {}
+                Ok(_)
+                    if checked
+                        .files
+                        .iter()
+                        .any(|file| source_matches(source, file)) => {}
+                Ok(_) => problems.push(Problem::error(
+                    &sources,
+                    format!("'{}' doesn't match any brick file", source),
+                )),
+                Err(error) => problems.push(invalid_regex(&sources, source, &error)),
+            }
+        } else if matches!(action, Action::InsertFile(_)) {
+            check_file(source, &sources, checked, problems);
+        }
+    }
+    if let Some(condition) = &action.common().when {
+        check_condition(condition, &format!("{}.when", location), checked, problems);
+    }
+    let mode = |mode: &str, problems: &mut Vec<Problem>| {
+        if let Err(error) = parse_mode(mode) {
+            problems.push(Problem::error(
+                format!("{}.mode", location),
+                error.to_string(),
+            ));
+        }
+    };
+    match action {
+        Action::InsertFile(action) => {
+            if let Some(value) = &action.mode {
+                mode(value, problems);
+            }
+        }
+        Action::Chmod(action) => mode(&action.mode, problems),
+        Action::ModifyFile(action) => {
+            let selector = format!("{}.selector", location);
+            if action.selector.is_empty() {
+                problems.push(Problem::error(&selector, "The selector is empty"));
+            } else if let Some(pattern) = action.selector.strip_prefix("re:")
+                && let Err(error) = Regex::new(pattern)
+            {
+                problems.push(invalid_regex(&selector, &action.selector, &error));
+            }
+            let content = format!("{}.content", location);
+            match &action.content {
+                None => problems.push(Problem::warning(
+                    &content,
+                    "There is no content, the file is left as it is",
+                )),
+                Some(text) => match text.strip_prefix("file:") {
+                    Some(name) => check_file(name, &content, checked, problems),
+                    None => check_template(text, &content, checked, problems),
+                },
+            }
+        }
+        Action::RunCommand(action) => {
+            let command = format!("{}.command", location);
+            match action.command.strip_prefix("file:") {
+                Some(name) => check_file(name, &command, checked, problems),
+                None if action.command.trim().is_empty() => {
+                    problems.push(Problem::error(&command, "The command is empty"))
+                }
+                None => {}
+            }
+        }
+        Action::PatchFile(action) => {
+            let patch = format!("{}.patch", location);
+            if let Err(error) = parse_hunks(&action.patch) {
+                problems.push(Problem::error(&patch, error.to_string()));
+            }
+            check_template(&action.patch, &patch, checked, problems);
+        }
+        Action::IncludeBrick(action) => {
+            for (name, value) in &action.variables {
+                let location = format!("{}.variables.{}", location, name);
+                check_template(value, &location, checked, problems);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn check_condition(
+    condition: &Condition,
+    location: &str,
+    checked: &Checked,
+    problems: &mut Vec<Problem>,
+) {
+    match condition {
+        Condition::Var { name, .. } if !checked.variables.contains(&name.as_str()) => {
+            problems.push(Problem::warning(
+                format!("{}.var.name", location),
+                format!(
+                    "'{}' is not a declared variable, it's only set with --var",
+                    name
+                ),
+            ));
+        }
+        Condition::All(conditions) | Condition::Any(conditions) => {
+            let kind = if matches!(condition, Condition::All(_)) {
+                "all"
+            } else {
+                "any"
+            };
+            for (index, condition) in conditions.iter().enumerate() {
+                let location = format!("{}.{}[{}]", location, kind, index);
+                check_condition(condition, &location, checked, problems);
+            }
+        }
+        Condition::Not(condition) => {
+            check_condition(condition, &format!("{}.not", location), checked, problems)
+        }
+        _ => {}
+    }
+}
+
+/// Report `name` if it's not a file of the brick
+fn check_file(
+    name: &str,
+    location: &str,
+    checked: &Checked,
+    problems: &mut Vec<Problem>,
+) {
+    if checked.files.iter().any(|file| file == name) {
+        return;
+    }
+    let message = match suggestion(name, checked.files.iter().map(String::as_str)) {
+        Some(suggestion) => format!(
+            "There is no brick file '{}', did you mean '{}'?",
+            name, suggestion
+        ),
+        None => format!("There is no brick file '{}'", name),
+    };
+    problems.push(Problem::error(location, message));
+}
+
+/// Report placeholders of variables the brick doesn't declare
+fn check_template(
+    text: &str,
+    location: &str,
+    checked: &Checked,
+    problems: &mut Vec<Problem>,
+) {
+    for (start, name) in placeholders(text) {
+        // `${{ ... }}` is used by GitHub workflows and isn't meant for crane
+        if text[..start].ends_with('$') || checked.variables.contains(&name) {
+            continue;
+        }
+        problems.push(Problem::warning(
+            location,
+            format!(
+                "'{{{{ {} }}}}' is not a declared variable, it's kept as is unless set with --var",
+                name
+            ),
+        ));
+    }
+}
+
+fn invalid_regex(location: &str, source: &str, error: &regex::Error) -> Problem {
+    // The error of the regex crate spans multiple lines, the last one says why
+    let reason = error.to_string();
+    let reason = reason.lines().last().unwrap_or_default().trim();
+    let reason = reason.trim_start_matches("error: ");
+    Problem::error(location, format!("Invalid regex '{}': {}", source, reason))
+}
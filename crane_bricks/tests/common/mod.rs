@@ -1,3 +1,4 @@
+use crane_bricks::brick::{Brick, BrickConfig};
 use std::{
     fs::File,
     io::{Read, Write},
@@ -41,3 +42,14 @@ pub fn file_content(path: &Path) -> String {
     file.read_to_string(&mut output).unwrap();
     output
 }
+
+/// Create a brick from an inline config, with the files of `insert_no_config`
+pub fn brick_from_str(config: &str) -> Brick {
+    brick_from_str_in("insert_no_config", config)
+}
+
+/// Create a brick from an inline config, with the files of the given test brick
+pub fn brick_from_str_in(brick: &str, config: &str) -> Brick {
+    let config: BrickConfig = toml::from_str(config).unwrap();
+    Brick::new_with_config(config, brick_dir(brick))
+}
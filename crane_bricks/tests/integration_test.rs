@@ -1,13 +1,20 @@
-use std::vec;
+use std::{cell::RefCell, collections::HashMap, rc::Rc, vec};
 
 use crane_bricks::{
     actions::{
         Action,
         common::Common,
-        insert_file::{FileExistsAction, InsertFileAction},
+        insert_file::{FileExistsAction, FileType, InsertFileAction},
     },
-    brick::{Brick, BrickConfig},
+    brick::{Brick, BrickConfig, Selection, bricks_in_dir, check_crane_version_in},
     context::ActionContext,
+    diagnostic::Diagnostic,
+    event::{Event, FileChange},
+    file_utils::sub_dirs,
+    project::ProjectRecord,
+    resolve::{check_conflicts, resolve_requirements},
+    template::render,
+    validate::validate_brick,
 };
 use log::debug;
 
@@ -40,6 +47,8 @@ action = "insert_file"
         vec![Action::InsertFile(InsertFileAction {
             common: Common::default(),
             if_file_exists: FileExistsAction::Append,
+            file_type: FileType::Auto,
+            ..Default::default()
         })],
     );
     assert_eq!(config_parsed, config);
@@ -52,7 +61,7 @@ fn test_insert_file() {
     let brick = Brick::try_from(brick_dir("insert_with_config")).unwrap();
     debug!("{:?}", brick);
 
-    let ctx = ActionContext { dry_run: false };
+    let ctx = ActionContext::new(false);
     let tmpdir = tempfile::tempdir().unwrap();
     brick.execute(&ctx, tmpdir.path()).unwrap();
     assert!(tmpdir.path().join("TEST_A").exists());
@@ -69,12 +78,204 @@ fn test_without_config() {
 
     assert_eq!(1, brick.config().actions().len());
 
-    let ctx = ActionContext { dry_run: false };
+    let ctx = ActionContext::new(false);
     let tmpdir = tempfile::tempdir().unwrap();
     brick.execute(&ctx, tmpdir.path()).unwrap();
     assert!(tmpdir.path().join("TEST_B").exists());
 }
 
+#[test]
+fn test_insert_binary_file() {
+    init_logger();
+
+    let brick = Brick::try_from(brick_dir("insert_binary")).unwrap();
+    let source = std::fs::read(brick_dir("insert_binary").join("favicon.ico")).unwrap();
+
+    let ctx = ActionContext::new(false);
+    let tmpdir = tempfile::tempdir().unwrap();
+    brick.execute(&ctx, tmpdir.path()).unwrap();
+    assert_eq!(
+        std::fs::read(tmpdir.path().join("favicon.ico")).unwrap(),
+        source
+    );
+
+    // Appending to an existing binary file must fail instead of corrupting it
+    assert!(brick.execute(&ctx, tmpdir.path()).is_err());
+    assert_eq!(
+        std::fs::read(tmpdir.path().join("favicon.ico")).unwrap(),
+        source
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_insert_keeps_mode() {
+    use std::os::unix::fs::PermissionsExt;
+    init_logger();
+
+    let brick = Brick::try_from(brick_dir("insert_executable")).unwrap();
+
+    let ctx = ActionContext::new(false);
+    let tmpdir = tempfile::tempdir().unwrap();
+    brick.execute(&ctx, tmpdir.path()).unwrap();
+    let meta = std::fs::metadata(tmpdir.path().join("release.sh")).unwrap();
+    assert_eq!(meta.permissions().mode() & 0o777, 0o755);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_insert_mode_override() {
+    use std::os::unix::fs::PermissionsExt;
+    init_logger();
+
+    let brick = brick_from_str(
+        r#"
+name = "test"
+
+[[actions]]
+action = "insert_file"
+mode = "0700"
+"#,
+    );
+
+    let ctx = ActionContext::new(false);
+    let tmpdir = tempfile::tempdir().unwrap();
+    brick.execute(&ctx, tmpdir.path()).unwrap();
+    let meta = std::fs::metadata(tmpdir.path().join("TEST_B")).unwrap();
+    assert_eq!(meta.permissions().mode() & 0o777, 0o700);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_insert_special_mode_bits() {
+    use std::os::unix::fs::PermissionsExt;
+    init_logger();
+
+    let brick_dir = tempfile::tempdir().unwrap();
+    let script = brick_dir.path().join("install.sh");
+    std::fs::write(&script, "#!/bin/sh\n").unwrap();
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o6755)).unwrap();
+
+    // Setuid and setgid of brick files are not copied into the project
+    let brick = Brick::try_from(brick_dir.path().to_path_buf()).unwrap();
+    assert_eq!(brick.files()[0].mode(), Some(0o755));
+    let ctx = ActionContext::new(false);
+    let tmpdir = tempfile::tempdir().unwrap();
+    brick.execute(&ctx, tmpdir.path()).unwrap();
+    let meta = std::fs::metadata(tmpdir.path().join("install.sh")).unwrap();
+    assert_eq!(meta.permissions().mode() & 0o7777, 0o755);
+
+    // Unless the brick asks for them
+    std::fs::write(
+        brick_dir.path().join("brick.toml"),
+        "name = \"test\"\n[[actions]]\naction = \"insert_file\"\nmode = \"2755\"\nif_file_exists = \"replace\"",
+    )
+    .unwrap();
+    let brick = Brick::try_from(brick_dir.path().to_path_buf()).unwrap();
+    brick.execute(&ctx, tmpdir.path()).unwrap();
+    let meta = std::fs::metadata(tmpdir.path().join("install.sh")).unwrap();
+    assert_eq!(meta.permissions().mode() & 0o7777, 0o2755);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_chmod() {
+    use std::os::unix::fs::PermissionsExt;
+    init_logger();
+
+    let brick = Brick::try_from(brick_dir("chmod")).unwrap();
+
+    let tmpdir = tempfile::tempdir().unwrap();
+    add_test_data(tmpdir.path(), "Test.toml");
+    let ctx = ActionContext::new(false);
+
+    brick.execute(&ctx, tmpdir.path()).unwrap();
+    let meta = std::fs::metadata(tmpdir.path().join("Test.toml")).unwrap();
+    assert_eq!(meta.permissions().mode() & 0o777, 0o600);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_insert_symlink() {
+    init_logger();
+
+    let brick = Brick::try_from(brick_dir("symlink")).unwrap();
+    let files = brick.files();
+    assert_eq!(1, files.len());
+    assert!(files[0].is_symlink());
+
+    let ctx = ActionContext::new(false);
+    let tmpdir = tempfile::tempdir().unwrap();
+    brick.execute(&ctx, tmpdir.path()).unwrap();
+    assert_eq!(
+        std::fs::read_link(tmpdir.path().join("rustfmt.toml")).unwrap(),
+        std::path::PathBuf::from("../../data/Test.toml")
+    );
+
+    // Same link again is fine, even with the default append
+    brick.execute(&ctx, tmpdir.path()).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_symlink_action() {
+    init_logger();
+
+    let brick = Brick::try_from(brick_dir("symlink_action")).unwrap();
+
+    let tmpdir = tempfile::tempdir().unwrap();
+    let ctx = ActionContext::new(false);
+    brick.execute(&ctx, tmpdir.path()).unwrap();
+    assert_eq!(
+        std::fs::read_link(tmpdir.path().join("rustfmt.toml")).unwrap(),
+        std::path::PathBuf::from("../../rustfmt.toml")
+    );
+
+    // A regular file is in the way, append can't turn it into a link
+    std::fs::remove_file(tmpdir.path().join("rustfmt.toml")).unwrap();
+    std::fs::write(tmpdir.path().join("rustfmt.toml"), "max_width = 90").unwrap();
+    assert!(brick.execute(&ctx, tmpdir.path()).is_err());
+}
+
+#[test]
+fn test_insert_destination() {
+    init_logger();
+
+    let brick = brick_from_str_in(
+        "insert_destination",
+        r#"
+name = "test"
+
+[[actions]]
+action = "insert_file"
+sources = ["LICENSE-MIT"]
+destination = "LICENSE"
+
+[[actions]]
+action = "insert_file"
+sources = ["LICENSE-MIT"]
+destination = "docs/legal/"
+
+[[actions]]
+action = "insert_file"
+sources = ["_gitignore"]
+dotfiles = true
+"#,
+    );
+
+    let ctx = ActionContext::new(false);
+    let tmpdir = tempfile::tempdir().unwrap();
+    brick.execute(&ctx, tmpdir.path()).unwrap();
+    assert_eq!(
+        file_content(&tmpdir.path().join("LICENSE")),
+        "MIT License\n"
+    );
+    assert!(tmpdir.path().join("docs/legal/LICENSE-MIT").exists());
+    assert!(!tmpdir.path().join("LICENSE-MIT").exists());
+    assert_eq!(file_content(&tmpdir.path().join(".gitignore")), "target/\n");
+    assert!(!tmpdir.path().join("_gitignore").exists());
+}
+
 #[test]
 fn test_modify_append() {
     init_logger();
@@ -83,7 +284,7 @@ fn test_modify_append() {
 
     let tmpdir = tempfile::tempdir().unwrap();
     add_test_data(tmpdir.path(), "Test.toml");
-    let ctx = ActionContext { dry_run: false };
+    let ctx = ActionContext::new(false);
 
     brick.execute(&ctx, tmpdir.path()).unwrap();
     let res_content = file_content(&tmpdir.path().join("Test.toml"));
@@ -91,6 +292,31 @@ fn test_modify_append() {
     assert!(res_content.contains("[dependencies]\nserde = \"1\"\n"))
 }
 
+#[test]
+fn test_modify_only_sources() {
+    init_logger();
+
+    let config = |only_sources: bool| {
+        format!(
+            "name = \"test\"\n[[actions]]\naction = \"modify_file\"\ntype = \"append\"\nsources = [\"Test.toml\"]\n\
+             content = \"\\nserde = \\\"1\\\"\"\nselector = \"[dependencies]\"\nonly_sources = {}",
+            only_sources
+        )
+    };
+    let tmpdir = tempfile::tempdir().unwrap();
+    add_test_data(tmpdir.path(), "Test.toml");
+    let ctx = ActionContext::new(false);
+
+    // The brick file TEST_B is modified as well, but it isn't in the project
+    let brick = brick_from_str_in("insert_no_config", &config(false));
+    assert!(brick.execute(&ctx, tmpdir.path()).is_err());
+
+    let brick = brick_from_str_in("insert_no_config", &config(true));
+    brick.execute(&ctx, tmpdir.path()).unwrap();
+    assert!(file_content(&tmpdir.path().join("Test.toml")).contains("serde = \"1\""));
+    assert!(!tmpdir.path().join("TEST_B").exists());
+}
+
 #[test]
 fn test_modify_prepend() {
     init_logger();
@@ -99,7 +325,7 @@ fn test_modify_prepend() {
 
     let tmpdir = tempfile::tempdir().unwrap();
     add_test_data(tmpdir.path(), "Test.toml");
-    let ctx = ActionContext { dry_run: false };
+    let ctx = ActionContext::new(false);
 
     brick.execute(&ctx, tmpdir.path()).unwrap();
     let res_content = file_content(&tmpdir.path().join("Test.toml"));
@@ -115,7 +341,7 @@ fn test_modify_replace() {
 
     let tmpdir = tempfile::tempdir().unwrap();
     add_test_data(tmpdir.path(), "Test.toml");
-    let ctx = ActionContext { dry_run: false };
+    let ctx = ActionContext::new(false);
 
     brick.execute(&ctx, tmpdir.path()).unwrap();
     let res_content = file_content(&tmpdir.path().join("Test.toml"));
@@ -124,6 +350,540 @@ fn test_modify_replace() {
     assert!(res_content.contains("[dev-dependencies]"));
 }
 
+#[test]
+fn test_delete_file_glob() {
+    init_logger();
+
+    let brick = brick_from_str(
+        r#"
+name = "test"
+
+[[actions]]
+action = "delete_file"
+sources = ["*.orig", "src/main.rs"]
+"#,
+    );
+    let tmpdir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(tmpdir.path().join("src")).unwrap();
+    for file in ["a.orig", "b.orig", "keep.txt", "src/main.rs"] {
+        std::fs::write(tmpdir.path().join(file), "").unwrap();
+    }
+
+    // Dry run must not touch anything
+    brick
+        .execute(&ActionContext::new(true), tmpdir.path())
+        .unwrap();
+    assert!(tmpdir.path().join("a.orig").exists());
+
+    brick
+        .execute(&ActionContext::new(false), tmpdir.path())
+        .unwrap();
+    assert!(!tmpdir.path().join("a.orig").exists());
+    assert!(!tmpdir.path().join("b.orig").exists());
+    assert!(!tmpdir.path().join("src/main.rs").exists());
+    assert!(tmpdir.path().join("keep.txt").exists());
+}
+
+#[test]
+fn test_move_copy_and_create_dir() {
+    init_logger();
+
+    let brick = brick_from_str(
+        r#"
+name = "test"
+
+[[actions]]
+action = "create_dir"
+path = "config/local"
+
+[[actions]]
+action = "move_file"
+from = ".env.example"
+to = ".env"
+
+[[actions]]
+action = "copy_file"
+from = "*.toml"
+to = "config/"
+"#,
+    );
+    let tmpdir = tempfile::tempdir().unwrap();
+    std::fs::write(tmpdir.path().join(".env.example"), "KEY=value").unwrap();
+    add_test_data(tmpdir.path(), "Test.toml");
+
+    brick
+        .execute(&ActionContext::new(false), tmpdir.path())
+        .unwrap();
+    assert!(tmpdir.path().join("config/local").is_dir());
+    assert!(!tmpdir.path().join(".env.example").exists());
+    assert_eq!(file_content(&tmpdir.path().join(".env")), "KEY=value");
+    assert!(tmpdir.path().join("Test.toml").exists());
+    assert!(tmpdir.path().join("config/Test.toml").exists());
+}
+
+#[test]
+fn test_rollback_on_failure() {
+    init_logger();
+
+    let brick = brick_from_str(
+        r#"
+name = "test"
+
+[[actions]]
+action = "create_dir"
+path = "new/dir"
+
+[[actions]]
+action = "move_file"
+from = "Test.toml"
+to = "Moved.toml"
+
+[[actions]]
+action = "delete_file"
+sources = ["keep.txt"]
+
+[[actions]]
+action = "modify_file"
+sources = ["Moved.toml"]
+type = "append"
+content = "never"
+selector = "does not exist"
+"#,
+    );
+    let tmpdir = tempfile::tempdir().unwrap();
+    add_test_data(tmpdir.path(), "Test.toml");
+    std::fs::write(tmpdir.path().join("keep.txt"), "keep me").unwrap();
+
+    assert!(
+        brick
+            .execute(&ActionContext::new(false), tmpdir.path())
+            .is_err()
+    );
+    assert!(!tmpdir.path().join("new").exists());
+    assert!(!tmpdir.path().join("Moved.toml").exists());
+    assert!(tmpdir.path().join("Test.toml").exists());
+    assert_eq!(file_content(&tmpdir.path().join("keep.txt")), "keep me");
+}
+
+#[test]
+fn test_when_conditions() {
+    init_logger();
+
+    let brick = brick_from_str(
+        r#"
+name = "test"
+
+[[variables]]
+name = "license"
+default = "mit"
+
+[[actions]]
+action = "create_dir"
+path = "with_cargo"
+when = { file_exists = "Cargo.toml" }
+
+[[actions]]
+action = "create_dir"
+path = "no_workspace"
+when = { all = [
+    { file_exists = "Test.toml" },
+    { not = { file_contains = { path = "Test.toml", text = "[workspace]" } } },
+] }
+
+[[actions]]
+action = "create_dir"
+path = "mit"
+when = { var = { name = "license", equals = "mit" } }
+
+[[actions]]
+action = "create_dir"
+path = "apache"
+when = { var = { name = "license", equals = "apache" } }
+
+[[actions]]
+action = "create_dir"
+path = "shell"
+when = { any = [ { command_exists = "sh" }, { env = { name = "CRANE_TEST_UNSET" } } ] }
+"#,
+    );
+    let tmpdir = tempfile::tempdir().unwrap();
+    add_test_data(tmpdir.path(), "Test.toml");
+
+    brick
+        .execute(&ActionContext::new(false), tmpdir.path())
+        .unwrap();
+    assert!(!tmpdir.path().join("with_cargo").exists());
+    assert!(tmpdir.path().join("no_workspace").exists());
+    assert!(tmpdir.path().join("mit").exists());
+    assert!(!tmpdir.path().join("apache").exists());
+    assert!(tmpdir.path().join("shell").exists());
+
+    // Variables set by the user overwrite the defaults
+    let tmpdir = tempfile::tempdir().unwrap();
+    let ctx = ActionContext::new(false).with_variables(HashMap::from([(
+        String::from("license"),
+        String::from("apache"),
+    )]));
+    brick.execute(&ctx, tmpdir.path()).unwrap();
+    assert!(!tmpdir.path().join("mit").exists());
+    assert!(tmpdir.path().join("apache").exists());
+}
+
+#[test]
+fn test_render_template() {
+    let variables = HashMap::from([(String::from("author"), String::from("ACME"))]);
+    assert_eq!(
+        render(
+            "(c) {{ author }}, {{author}}! ${{ github.ref }} {{",
+            &variables
+        ),
+        "(c) ACME, ACME! ${{ github.ref }} {{"
+    );
+}
+
+#[test]
+fn test_insert_template() {
+    init_logger();
+
+    let brick = brick_from_str(
+        r#"
+name = "test"
+
+[[variables]]
+name = "name"
+default = "crane"
+
+[[actions]]
+action = "insert_file"
+
+[[actions]]
+action = "modify_file"
+type = "replace"
+selector = "World"
+content = "{{ name }}"
+"#,
+    );
+    let tmpdir = tempfile::tempdir().unwrap();
+    brick
+        .execute(&ActionContext::new(false), tmpdir.path())
+        .unwrap();
+    assert_eq!(file_content(&tmpdir.path().join("TEST_B")), "Hello crane");
+}
+
+fn brick_requiring(name: &str, requires: &[&str]) -> Brick {
+    let config: BrickConfig =
+        toml::from_str(&format!("name = \"{}\"\nrequires = {:?}", name, requires))
+            .unwrap();
+    Brick::new_with_config(config, brick_dir(name))
+}
+
+#[test]
+fn test_resolve_requirements() {
+    let bricks = vec![
+        brick_requiring("rustfmt", &["rust-base"]),
+        brick_requiring("clippy", &["rust-base", "rustfmt"]),
+        brick_requiring("rust-base", &[]),
+        brick_requiring("mit", &[]),
+    ];
+    let requested = vec![&bricks[1], &bricks[3], &bricks[0]];
+    let ordered: Vec<&str> = resolve_requirements(&requested, &bricks)
+        .unwrap()
+        .iter()
+        .map(|brick| brick.name())
+        .collect();
+    assert_eq!(ordered, vec!["rust-base", "rustfmt", "clippy", "mit"]);
+}
+
+#[test]
+fn test_resolve_requirements_errors() {
+    let bricks = vec![
+        brick_requiring("a", &["b"]),
+        brick_requiring("b", &["c"]),
+        brick_requiring("c", &["a"]),
+        brick_requiring("d", &["missing"]),
+    ];
+    let error = resolve_requirements(&[&bricks[0]], &bricks)
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("a → b → c → a"), "{}", error);
+
+    let error = resolve_requirements(&[&bricks[3]], &bricks)
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("'d' requires 'missing'"), "{}", error);
+}
+
+#[test]
+fn test_check_conflicts() {
+    let mit = brick_from_str("name = \"mit\"\nprovides = \"license\"");
+    let apache = brick_from_str("name = \"apache\"\nprovides = [\"license\"]");
+    let github_ci = brick_from_str("name = \"github-ci\"\nconflicts = [\"gitlab-ci\"]");
+    let gitlab_ci = brick_from_str("name = \"gitlab-ci\"");
+    let no_license = brick_from_str("name = \"unlicensed\"\nconflicts = [\"license\"]");
+
+    assert!(check_conflicts(&[&mit, &github_ci], &[]).is_ok());
+
+    let error = check_conflicts(&[&mit, &apache], &[]).unwrap_err();
+    assert!(error.to_string().contains("'mit' conflicts with 'apache'"));
+    assert!(error.to_string().contains("both provide 'license'"));
+    let bsd = brick_from_str("name = \"bsd\"\nprovides = \"License\"");
+    assert!(check_conflicts(&[&mit, &bsd], &[]).is_err());
+
+    assert!(check_conflicts(&[&gitlab_ci, &github_ci], &[]).is_err());
+    assert!(check_conflicts(&[&no_license, &apache], &[]).is_err());
+
+    // Conflicts with bricks that were applied in an earlier run
+    let tmpdir = tempfile::tempdir().unwrap();
+    let ctx = ActionContext::new(false);
+    let mut record = ProjectRecord::load(tmpdir.path()).unwrap();
+    record.add(&mit);
+    record.save(&ctx, tmpdir.path()).unwrap();
+
+    let record = ProjectRecord::load(tmpdir.path()).unwrap();
+    let error = check_conflicts(&[&apache], record.applied()).unwrap_err();
+    assert!(error.to_string().contains("already applied"));
+    // Applying the same brick again is fine
+    assert!(check_conflicts(&[&mit], record.applied()).is_ok());
+}
+
+#[test]
+fn test_extends() {
+    init_logger();
+
+    let brick = Brick::try_from(brick_dir("ci_nightly")).unwrap();
+    assert_eq!(brick.base_paths(), &[brick_dir("ci_base")]);
+    assert_eq!(2, brick.config().actions().len());
+    assert_eq!("toolchain", brick.config().variables()[0].name());
+
+    let tmpdir = tempfile::tempdir().unwrap();
+    brick
+        .execute(&ActionContext::new(false), tmpdir.path())
+        .unwrap();
+    // Own files overwrite the ones of the base brick
+    assert_eq!(
+        file_content(&tmpdir.path().join("ci.yml")),
+        "toolchain: nightly\n"
+    );
+    assert!(tmpdir.path().join("README.md").exists());
+    assert!(tmpdir.path().join(".github").is_dir());
+
+    // Bricks in a directory are resolved as well
+    let bricks = bricks_in_dir(&test_dir().join("bricks"));
+    let brick = bricks
+        .iter()
+        .find(|brick| brick.name() == "ci-nightly")
+        .unwrap();
+    assert_eq!(2, brick.config().actions().len());
+}
+
+#[test]
+fn test_extends_replace_and_cycle() {
+    let base = brick_from_str("name = \"base\"\n[[actions]]\naction = \"insert_file\"");
+    let child = brick_from_str(
+        "name = \"child\"\nextends = \"base\"\nextend_actions = \"replace\"\n[[actions]]\naction = \"create_dir\"\npath = \"x\"",
+    );
+    let resolved = child.resolve_extends(&[base]).unwrap();
+    assert_eq!(1, resolved.config().actions().len());
+    assert_eq!("create_dir", resolved.config().actions()[0].name());
+
+    let a = brick_from_str("name = \"a\"\nextends = \"b\"");
+    let b = brick_from_str("name = \"b\"\nextends = \"a\"");
+    let error = a.resolve_extends(&[a.clone(), b]).unwrap_err();
+    assert!(error.to_string().contains("a → b → a"), "{}", error);
+    assert!(child.resolve_extends(&[]).is_err());
+}
+
+#[test]
+fn test_extends_chain() {
+    let brick = |name: &str, extends: &str| {
+        brick_from_str(&format!(
+            "name = \"{}\"\n{}\n[[actions]]\naction = \"run_command\"\ncommand = \"echo {}\"",
+            name, extends, name
+        ))
+    };
+    let commands = |brick: &Brick| -> Vec<String> {
+        brick
+            .config()
+            .actions()
+            .iter()
+            .map(|action| match action {
+                Action::RunCommand(run) => run.command.clone(),
+                other => other.name().to_string(),
+            })
+            .collect()
+    };
+    let root = brick("root", "");
+    let mid = brick("mid", "extends = \"root\"\nextend_actions = \"replace\"");
+    let child = brick("child", "extends = \"mid\"");
+    let bricks = [root.clone(), mid.clone(), child.clone()];
+
+    // Each brick combines its actions with the resolved base only
+    let resolved = child.resolve_extends(&bricks).unwrap();
+    assert_eq!(commands(&resolved), ["echo mid", "echo child"]);
+    assert_eq!(resolved.base_paths().len(), 2);
+
+    let mid = brick("mid", "extends = \"root\"\nextend_actions = \"prepend\"");
+    let bricks = [root, mid, child.clone()];
+    let resolved = child.resolve_extends(&bricks).unwrap();
+    assert_eq!(commands(&resolved), ["echo mid", "echo root", "echo child"]);
+}
+
+#[test]
+fn test_extends_crane_version() {
+    let brick = |name: &str, extends: Option<&str>, version: Option<&str>| {
+        let mut config = format!("name = \"{}\"\n", name);
+        if let Some(extends) = extends {
+            config.push_str(&format!("extends = \"{}\"\n", extends));
+        }
+        if let Some(version) = version {
+            config.push_str(&format!("min_crane_version = \"{}\"\n", version));
+        }
+        brick_from_str(&config)
+    };
+    let min_version = |child: Option<&str>, base: Option<&str>| {
+        brick("child", Some("base"), child)
+            .resolve_extends(&[brick("base", None, base)])
+            .unwrap()
+            .config()
+            .min_crane_version()
+            .map(String::from)
+    };
+    // The newer of both versions is needed
+    assert_eq!(
+        min_version(Some("0.3"), Some("0.10.1")).as_deref(),
+        Some("0.10.1")
+    );
+    assert_eq!(
+        min_version(Some("1.2"), Some("0.10.1")).as_deref(),
+        Some("1.2")
+    );
+    assert_eq!(min_version(None, Some("0.4")).as_deref(), Some("0.4"));
+    assert_eq!(min_version(Some("0.4"), None).as_deref(), Some("0.4"));
+    assert_eq!(min_version(None, None), None);
+}
+
+#[test]
+fn test_variants_and_features() {
+    init_logger();
+
+    let brick = Brick::try_from(brick_dir("license")).unwrap();
+
+    // Without a selection, the default variant is used
+    let default = brick.select(&Selection::default()).unwrap();
+    let tmpdir = tempfile::tempdir().unwrap();
+    default
+        .execute(&ActionContext::new(false), tmpdir.path())
+        .unwrap();
+    assert!(tmpdir.path().join("LICENSE-MIT").exists());
+    assert!(tmpdir.path().join("README.md").exists());
+    assert!(!tmpdir.path().join("LICENSE-APACHE").exists());
+    assert!(!tmpdir.path().join("NOTICE").exists());
+    assert!(!tmpdir.path().join("docs").exists());
+
+    let apache = brick
+        .select(&Selection::new(
+            Some(String::from("apache")),
+            vec![String::from("notice"), String::from("docs")],
+        ))
+        .unwrap();
+    let tmpdir = tempfile::tempdir().unwrap();
+    apache
+        .execute(&ActionContext::new(false), tmpdir.path())
+        .unwrap();
+    assert!(!tmpdir.path().join("LICENSE-MIT").exists());
+    assert!(tmpdir.path().join("LICENSE-APACHE").exists());
+    assert!(tmpdir.path().join("NOTICE").exists());
+    assert!(tmpdir.path().join("docs").is_dir());
+
+    assert!(
+        brick
+            .select(&Selection::new(Some(String::from("gpl")), vec![]))
+            .is_err()
+    );
+    assert!(
+        brick
+            .select(&Selection::new(None, vec![String::from("missing")]))
+            .is_err()
+    );
+}
+
+#[test]
+fn test_include_brick() {
+    init_logger();
+
+    let outer = brick_from_str(
+        r#"
+name = "outer"
+
+[[variables]]
+name = "dir"
+default = "included"
+
+[[actions]]
+action = "include_brick"
+brick = "license"
+variant = "apache"
+
+[[actions]]
+action = "include_brick"
+brick = "make-dir"
+variables = { name = "{{ dir }}" }
+"#,
+    );
+    let make_dir = brick_from_str(
+        r#"
+name = "make-dir"
+
+[[actions]]
+action = "create_dir"
+path = "included_dir"
+when = { var = { name = "name", equals = "included" } }
+"#,
+    );
+    let license = Brick::try_from(brick_dir("license")).unwrap();
+    let ctx = ActionContext::new(false).with_bricks(vec![license, make_dir]);
+
+    let tmpdir = tempfile::tempdir().unwrap();
+    outer.execute(&ctx, tmpdir.path()).unwrap();
+    assert!(tmpdir.path().join("LICENSE-APACHE").exists());
+    assert!(!tmpdir.path().join("LICENSE-MIT").exists());
+    // The rendered variable reached the included brick
+    assert!(tmpdir.path().join("included_dir").is_dir());
+}
+
+#[test]
+fn test_include_brick_recursion_and_rollback() {
+    init_logger();
+
+    let a = brick_from_str(
+        r#"
+name = "a"
+
+[[actions]]
+action = "create_dir"
+path = "from_a"
+
+[[actions]]
+action = "include_brick"
+brick = "b"
+"#,
+    );
+    let b = brick_from_str(
+        r#"
+name = "b"
+
+[[actions]]
+action = "include_brick"
+brick = "a"
+"#,
+    );
+    let ctx = ActionContext::new(false).with_bricks(vec![a.clone(), b]);
+    let tmpdir = tempfile::tempdir().unwrap();
+    let error = a.execute(&ctx, tmpdir.path()).unwrap_err();
+    assert!(error.to_string().contains("a → b → a"), "{}", error);
+    assert!(!tmpdir.path().join("from_a").exists());
+}
+
 #[test]
 fn test_command() {
     init_logger();
@@ -131,8 +891,568 @@ fn test_command() {
     let brick = Brick::try_from(brick_dir("run_command")).unwrap();
 
     let tmpdir = tempfile::tempdir().unwrap();
-    let ctx = ActionContext { dry_run: false };
+    let ctx = ActionContext::new(false);
 
     brick.execute(&ctx, tmpdir.path()).unwrap();
     assert!(tmpdir.path().join("test.txt").exists());
 }
+
+#[test]
+fn test_dry_run_planned_files() {
+    init_logger();
+
+    let brick = brick_from_str(
+        r#"
+name = "test"
+
+[[actions]]
+action = "insert_file"
+
+[[actions]]
+action = "modify_file"
+type = "append"
+selector = "Hello"
+content = ","
+
+[[actions]]
+action = "delete_file"
+sources = ["old.txt"]
+"#,
+    );
+    let tmpdir = tempfile::tempdir().unwrap();
+    std::fs::write(tmpdir.path().join("old.txt"), "old").unwrap();
+
+    let context = ActionContext::new(true);
+    brick.execute(&context, tmpdir.path()).unwrap();
+    assert!(!tmpdir.path().join("TEST_B").exists());
+    assert!(tmpdir.path().join("old.txt").exists());
+    assert_eq!(
+        context.planned_files(),
+        vec![
+            (tmpdir.path().join("TEST_B"), Some(b"Hello, World".to_vec())),
+            (tmpdir.path().join("old.txt"), None),
+        ]
+    );
+}
+
+#[test]
+fn test_metadata_and_crane_version() {
+    let config: BrickConfig = toml::from_str(
+        r#"
+name = "license"
+description = "Common licenses"
+tags = ["legal"]
+authors = ["ACME"]
+version = "1.2.0"
+homepage = "https://example.com"
+min_crane_version = "0.3"
+"#,
+    )
+    .unwrap();
+    assert_eq!(config.description(), Some("Common licenses"));
+    assert_eq!(config.tags(), ["legal"]);
+    assert_eq!(config.authors(), ["ACME"]);
+    assert_eq!(config.version(), Some("1.2.0"));
+    assert_eq!(config.homepage(), Some("https://example.com"));
+
+    assert!(config.check_crane_version("0.3.0").is_ok());
+    assert!(config.check_crane_version("1.0.0").is_ok());
+    let error = config.check_crane_version("0.2.5").unwrap_err();
+    assert!(
+        error.to_string().contains("needs crane 0.3 or newer"),
+        "{}",
+        error
+    );
+
+    // A brick for a newer crane is checked even if it can't be loaded
+    let tmpdir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        tmpdir.path().join("brick.toml"),
+        r#"
+name = "future"
+min_crane_version = "0.3"
+
+[[actions]]
+action = "teleport_file"
+"#,
+    )
+    .unwrap();
+    assert!(Brick::try_from(tmpdir.path().to_path_buf()).is_err());
+    assert!(check_crane_version_in(tmpdir.path(), "0.3.0").is_ok());
+    let error = check_crane_version_in(tmpdir.path(), "0.2.5").unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("Brick 'future' needs crane 0.3 or newer"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn test_events() {
+    init_logger();
+
+    let brick = brick_from_str(
+        r#"
+name = "test"
+
+[[actions]]
+action = "insert_file"
+
+[[actions]]
+action = "run_command"
+command = "true"
+when = { file_exists = "missing.txt" }
+"#,
+    );
+    let tmpdir = tempfile::tempdir().unwrap();
+    let events: Rc<RefCell<Vec<Event>>> = Rc::new(RefCell::new(Vec::new()));
+    let collected = Rc::clone(&events);
+    let context = ActionContext::new(false)
+        .with_listener(move |event| collected.borrow_mut().push(event.clone()));
+    brick.execute(&context, tmpdir.path()).unwrap();
+
+    let name = String::from("test");
+    assert_eq!(
+        *events.borrow(),
+        vec![
+            Event::BrickStarted {
+                brick: name.clone()
+            },
+            Event::FileChanged {
+                path: tmpdir.path().join("TEST_B"),
+                change: FileChange::Created,
+            },
+            // The mode of the brick file is copied
+            Event::FileChanged {
+                path: tmpdir.path().join("TEST_B"),
+                change: FileChange::ModeChanged,
+            },
+            Event::ActionExecuted {
+                brick: name.clone(),
+                action: String::from("insert_file"),
+            },
+            Event::ActionSkipped {
+                brick: name.clone(),
+                action: String::from("run_command"),
+                reason: String::from("condition not met: file 'missing.txt' exists"),
+            },
+            Event::BrickFinished {
+                brick: name,
+                error: None,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_action_descriptions() {
+    let config: BrickConfig = toml::from_str(
+        r#"
+name = "test"
+
+[[actions]]
+action = "insert_file"
+sources = ["LICENSE"]
+if_file_exists = "replace"
+destination = "docs/"
+
+[[actions]]
+action = "modify_file"
+sources = ["Cargo.toml"]
+type = "append"
+selector = "[dependencies]"
+content = "\nserde = \"1\""
+
+[[actions]]
+action = "run_command"
+command = "cargo fmt"
+when = { command_exists = "cargo" }
+"#,
+    )
+    .unwrap();
+    let descriptions: Vec<String> = config
+        .actions()
+        .iter()
+        .map(|action| action.to_string())
+        .collect();
+    assert_eq!(
+        descriptions,
+        vec![
+            "Insert 'LICENSE' into 'docs/', replace if it exists",
+            "Append \"\\nserde = \\\"1\\\"\" after '[dependencies]' in the brick files, 'Cargo.toml'",
+            "Run `cargo fmt`, only if command 'cargo' exists",
+        ]
+    );
+}
+
+#[test]
+fn test_serialize_brick_config() {
+    let config: BrickConfig = toml::from_str(
+        r##"
+name = "test"
+description = "A test brick"
+provides = "license"
+
+[[variables]]
+name = "author"
+default = "ACME"
+
+[[actions]]
+action = "insert_file"
+if_file_exists = "replace"
+dotfiles = true
+
+[[actions]]
+action = "modify_file"
+sources = ["Cargo.toml"]
+type = "prepend"
+selector = "[dependencies]"
+content = "# Dependencies"
+
+[[actions]]
+action = "run_command"
+command = "cargo fmt"
+when = { all = [{ command_exists = "cargo" }, { var = { name = "author" } }] }
+
+[[features]]
+name = "docs"
+files = ["README.md"]
+"##,
+    )
+    .unwrap();
+    let serialized = toml::to_string(&config).unwrap();
+    // Defaults are left out
+    assert!(!serialized.contains("file_type"));
+    assert!(!serialized.contains("extend_actions"));
+    assert_eq!(toml::from_str::<BrickConfig>(&serialized).unwrap(), config);
+}
+
+#[test]
+fn test_insert_nested_files() {
+    init_logger();
+
+    let brick = Brick::try_from(brick_dir("insert_nested")).unwrap();
+    let mut names: Vec<String> = brick
+        .files()
+        .iter()
+        .map(|file| file.name().to_string())
+        .collect();
+    names.sort();
+    assert_eq!(names, vec!["config/_env", "src/main.rs"]);
+
+    let ctx = ActionContext::new(false);
+    let tmpdir = tempfile::tempdir().unwrap();
+    brick.execute(&ctx, tmpdir.path()).unwrap();
+    assert_eq!(
+        file_content(&tmpdir.path().join("src/main.rs")),
+        "fn main() {}\n"
+    );
+    assert!(tmpdir.path().join("config/.env").exists());
+
+    // Directories of version control, crane and build tools are skipped
+    let tmpdir = tempfile::tempdir().unwrap();
+    for name in [
+        "src/lib.rs",
+        "docs/target.md",
+        ".git/HEAD",
+        ".crane/applied.toml",
+        "target/debug/build.log",
+        "web/node_modules/left-pad/index.js",
+    ] {
+        let path = tmpdir.path().join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, "").unwrap();
+    }
+    let brick = Brick::try_from(tmpdir.path().to_path_buf()).unwrap();
+    let mut names: Vec<String> = brick
+        .files()
+        .iter()
+        .map(|file| file.name().to_string())
+        .collect();
+    names.sort();
+    assert_eq!(names, vec!["docs/target.md", "src/lib.rs"]);
+}
+
+#[test]
+fn test_patch_file() {
+    init_logger();
+
+    let brick = brick_from_str(
+        r#"
+name = "test"
+
+[[actions]]
+action = "patch_file"
+path = "main.rs"
+patch = """
+--- a/main.rs
++++ b/main.rs
+@@ -1,3 +1,3 @@
+ fn main() {
+-    println!("Hello, world!");
++    println!("Hello, {{ name }}!");
+ }
+@@ -4,2 +4,3 @@
+ 
+ fn helper() {}
++fn added() {}
+\\ No newline at end of file
+"""
+"#,
+    );
+    let tmpdir = tempfile::tempdir().unwrap();
+    // A line was added before the first hunk since the patch was made
+    std::fs::write(
+        tmpdir.path().join("main.rs"),
+        "use std::fs;\nfn main() {\n    println!(\"Hello, world!\");\n}\n\nfn helper() {}\n",
+    )
+    .unwrap();
+    let mut variables = HashMap::new();
+    variables.insert(String::from("name"), String::from("crane"));
+    brick
+        .execute(&ActionContext::new(false).child(variables), tmpdir.path())
+        .unwrap();
+    assert_eq!(
+        file_content(&tmpdir.path().join("main.rs")),
+        "use std::fs;\nfn main() {\n    println!(\"Hello, crane!\");\n}\n\nfn helper() {}\nfn added() {}"
+    );
+
+    std::fs::write(tmpdir.path().join("main.rs"), "fn other() {}\n").unwrap();
+    assert!(
+        brick
+            .execute(&ActionContext::new(false), tmpdir.path())
+            .is_err()
+    );
+}
+
+#[test]
+fn test_validate_brick() {
+    init_logger();
+
+    let tmpdir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        tmpdir.path().join("README.md"),
+        "# {{ name }} by {{ author }}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        tmpdir.path().join("brick.toml"),
+        r#"
+name = "broken"
+default_variant = "mit"
+
+[[variables]]
+name = "name"
+
+[[actions]]
+action = "insert_file"
+sourcs = [ "README.md" ]
+if_file_exist = "replace"
+
+[[actions]]
+action = "insert_file"
+sources = [ "README.mdx", "re:[a-z" ]
+when = { all = [ { var = { name = "license" } } ] }
+
+[[actions]]
+action = "modify_file"
+type = "append"
+selector = ""
+content = "file:missing.txt"
+"#,
+    )
+    .unwrap();
+    let problems: Vec<String> = validate_brick(tmpdir.path())
+        .iter()
+        .map(|problem| problem.to_string())
+        .collect();
+    assert_eq!(
+        problems,
+        [
+            "default_variant: There is no variant 'mit'",
+            "actions[0].sourcs: Unknown key 'sourcs', did you mean 'sources'?",
+            "actions[0].if_file_exist: Unknown key 'if_file_exist', did you mean 'if_file_exists'?",
+            "actions[1].sources: There is no brick file 'README.mdx', did you mean 'README.md'?",
+            "actions[1].sources: Invalid regex 're:[a-z': unclosed character class",
+            "actions[1].when.all[0].var.name: 'license' is not a declared variable, it's only set with --var",
+            "actions[2].selector: The selector is empty",
+            "actions[2].content: There is no brick file 'missing.txt'",
+            "README.md: '{{ author }}' is not a declared variable, it's kept as is unless set with --var",
+        ]
+    );
+
+    // Unknown actions and conditions get a suggestion instead of the parse
+    // error, and the rest of the brick is still checked
+    std::fs::write(
+        tmpdir.path().join("brick.toml"),
+        r#"
+name = "broken"
+
+[[actions]]
+action = "insert_fil"
+
+[[actions]]
+action = "run_command"
+command = "ls"
+when = { not = { file_exist = "Cargo.toml" } }
+"#,
+    )
+    .unwrap();
+    let problems = validate_brick(tmpdir.path());
+    let messages: Vec<&str> = problems
+        .iter()
+        .map(|problem| problem.message.as_str())
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            "Unknown action 'insert_fil', did you mean 'insert_file'?",
+            "Unknown condition 'file_exist', did you mean 'file_exists'?",
+            "'{{ name }}' is not a declared variable, it's kept as is unless set with --var",
+            "'{{ author }}' is not a declared variable, it's kept as is unless set with --var",
+        ]
+    );
+    assert!(problems[..2].iter().all(|problem| problem.span.is_some()));
+
+    // Other parse errors are still reported next to them
+    std::fs::write(
+        tmpdir.path().join("brick.toml"),
+        r#"
+name = "broken"
+tags = 5
+
+[[actions]]
+action = "insert_fil"
+
+[[actions]]
+action = "run_command"
+command = "ls"
+when = { any = [ { file_exist = "Cargo.toml" }, { env = { name = "CI" } } ] }
+"#,
+    )
+    .unwrap();
+    let problems: Vec<String> = validate_brick(tmpdir.path())
+        .iter()
+        .map(|problem| problem.to_string())
+        .collect();
+    assert_eq!(
+        problems,
+        [
+            "invalid type: integer `5`, expected a sequence",
+            "actions[0].action: Unknown action 'insert_fil', did you mean 'insert_file'?",
+            "actions[1].when.any[0].file_exist: Unknown condition 'file_exist', did you mean 'file_exists'?",
+        ]
+    );
+
+    // An action that can't be parsed is reported at itself, not at the first
+    // action, and the other actions are still checked
+    std::fs::write(
+        tmpdir.path().join("brick.toml"),
+        r#"
+name = "broken"
+
+[[variables]]
+name = "name"
+
+[[variables]]
+name = "author"
+
+[[actions]]
+action = "insert_file"
+
+[[actions]]
+action = "insert_file"
+sources = [ "re:[a-z" ]
+
+[[actions]]
+action = "run_command"
+command = "file:missing.sh"
+
+[[actions]]
+action = "modify_file"
+type = "append"
+content = "text"
+"#,
+    )
+    .unwrap();
+    let problems = validate_brick(tmpdir.path());
+    let messages: Vec<String> =
+        problems.iter().map(|problem| problem.to_string()).collect();
+    assert_eq!(
+        messages,
+        [
+            "actions[1].sources: Invalid regex 're:[a-z': unclosed character class",
+            "actions[2].command: There is no brick file 'missing.sh'",
+            "actions[3]: missing field `selector`",
+        ]
+    );
+    assert_eq!(problems[2].line, Some(21));
+    let error = Brick::try_from(tmpdir.path().to_path_buf()).unwrap_err();
+    let diagnostic = error.downcast_ref::<Diagnostic>().unwrap();
+    assert_eq!(diagnostic.line(), Some(21));
+    assert_eq!(diagnostic.message(), "actions[3]: missing field `selector`");
+
+    // All test bricks are valid
+    for dir in sub_dirs(&test_dir().join("bricks")).unwrap() {
+        let problems = validate_brick(&dir);
+        assert!(
+            problems.iter().all(|problem| !problem.is_error()),
+            "{}: {:?}",
+            dir.display(),
+            problems
+        );
+    }
+}
+
+#[test]
+fn test_insert_regex_sources() {
+    init_logger();
+
+    let brick = brick_from_str_in(
+        "insert_nested",
+        "name = \"nested\"\n[[actions]]\naction = \"insert_file\"\nsources = [ \"re:src/.+\\\\.rs\" ]",
+    );
+    let tmpdir = tempfile::tempdir().unwrap();
+    brick
+        .execute(&ActionContext::new(false), tmpdir.path())
+        .unwrap();
+    assert!(tmpdir.path().join("src/main.rs").exists());
+    assert!(!tmpdir.path().join("config").exists());
+}
+
+#[test]
+fn test_parse_error_diagnostic() {
+    init_logger();
+
+    let tmpdir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        tmpdir.path().join("brick.toml"),
+        "name = \"broken\"\n\n# Must be a list\ntags = \"rust\"\n",
+    )
+    .unwrap();
+    let error = Brick::try_from(tmpdir.path().to_path_buf()).unwrap_err();
+    let diagnostic = error.downcast_ref::<Diagnostic>().unwrap();
+    assert_eq!(diagnostic.line(), Some(4));
+    assert_eq!(diagnostic.column(), Some(8));
+    assert_eq!(
+        diagnostic.snippet(),
+        Some((
+            String::from("4 | tags = \"rust\""),
+            String::from("  |        ^^^^^^")
+        ))
+    );
+    assert!(
+        error.to_string().starts_with(&format!(
+            "{}:4:8: ",
+            tmpdir.path().join("brick.toml").display()
+        )),
+        "{}",
+        error
+    );
+
+    // Problems found by validating point at their line as well
+    let problems = validate_brick(tmpdir.path());
+    assert_eq!((problems[0].line, problems[0].column), (Some(4), Some(8)));
+}